@@ -0,0 +1,45 @@
+//! Procedural macros for `kg-symbol`, re-exported by the main crate with `macros` feature enabled.
+
+extern crate proc_macro;
+
+use proc_macro::{Literal, TokenStream, TokenTree};
+
+/// Returns `true` if `lit` is a string literal, raw or not.
+fn is_str_literal(lit: &Literal) -> bool {
+    let repr = lit.to_string();
+    repr.starts_with('"') || (repr.starts_with('r') && repr[1..].trim_start_matches('#').starts_with('"'))
+}
+
+fn error(message: &str) -> TokenStream {
+    format!("compile_error!({:?})", message).parse().unwrap()
+}
+
+/// Returns a `Symbol` for a string literal, interned on first use and cached in a static.
+///
+/// Hash of the literal is computed at compile time by `kg_symbol::hash_str`, so the first use
+/// does not rehash the string, and subsequent uses do not access the global symbol table at all.
+///
+/// ```ignore
+/// let s = kg_symbol::symbol!("foo");
+/// assert_eq!(s, "foo");
+/// ```
+#[proc_macro]
+pub fn symbol(input: TokenStream) -> TokenStream {
+    let mut tokens = input.into_iter();
+    let lit = match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Literal(lit)), None) => lit,
+        (Some(TokenTree::Group(g)), None) if g.delimiter() == proc_macro::Delimiter::None => {
+            // literal passed through `macro_rules!` as `$x:expr`
+            return symbol(g.stream());
+        }
+        _ => return error("symbol! expects a single string literal"),
+    };
+    if !is_str_literal(&lit) {
+        return error("symbol! expects a single string literal");
+    }
+
+    format!(
+        "{{ static SYMBOL: ::kg_symbol::StaticSymbol = ::kg_symbol::StaticSymbol::new({}); SYMBOL.get() }}",
+        lit,
+    ).parse().unwrap()
+}
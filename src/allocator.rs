@@ -0,0 +1,354 @@
+// allocation functions are only used by the global symbol table
+#![cfg_attr(not(feature = "global"), allow(dead_code))]
+
+#[cfg(feature = "nightly")]
+use std::alloc::{Allocator, Global};
+use std::alloc::Layout;
+use std::ptr::NonNull;
+
+/// Error returned when memory for a symbol could not be allocated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError;
+
+impl std::fmt::Display for AllocError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("symbol allocation failed")
+    }
+}
+
+impl std::error::Error for AllocError {}
+
+#[cfg(feature = "test-util")]
+mod failure {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNTDOWN: AtomicUsize = AtomicUsize::new(0);
+
+    /// Makes the `n`-th subsequent symbol allocation fail, counting from 1.
+    /// Passing 0 disables failure injection.
+    pub fn fail_nth_allocation(n: usize) {
+        COUNTDOWN.store(n, Ordering::SeqCst);
+    }
+
+    pub(super) fn should_fail() -> bool {
+        COUNTDOWN.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)) == Ok(1)
+    }
+}
+
+#[cfg(feature = "test-util")]
+pub use self::failure::fail_nth_allocation;
+
+#[cfg(feature = "arena-allocator")]
+mod arena {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use parking_lot::Mutex;
+
+    /// Size of chunks, which are also aligned to their size, so that chunk of any block
+    /// can be found by masking its address.
+    const CHUNK_SIZE: usize = 64 * 1024;
+
+    /// Larger blocks are allocated directly from the global allocator.
+    const MAX_BLOCK_SIZE: usize = CHUNK_SIZE / 16;
+
+    const MAX_BLOCK_ALIGN: usize = 64;
+
+    /// Header at the start of every chunk.
+    struct ChunkHdr {
+        /// Number of live blocks, plus one while the chunk is the current one.
+        live: AtomicUsize,
+    }
+
+    struct Arena {
+        chunk: Option<NonNull<u8>>,
+        offset: usize,
+    }
+
+    unsafe impl Send for Arena {}
+
+    static ARENA: Mutex<Arena> = Mutex::new(Arena { chunk: None, offset: 0 });
+
+    #[inline]
+    fn in_arena(layout: Layout) -> bool {
+        layout.size() <= MAX_BLOCK_SIZE && layout.align() <= MAX_BLOCK_ALIGN
+    }
+
+    #[inline]
+    fn chunk_layout() -> Layout {
+        unsafe { Layout::from_size_align_unchecked(CHUNK_SIZE, CHUNK_SIZE) }
+    }
+
+    #[inline]
+    fn chunk_header<'a>(chunk: NonNull<u8>) -> &'a ChunkHdr {
+        unsafe { &*(chunk.as_ptr() as *const ChunkHdr) }
+    }
+
+    /// Releases one reference to the chunk, deallocating it when it was the last one.
+    unsafe fn release_chunk(chunk: NonNull<u8>) {
+        if chunk_header(chunk).live.fetch_sub(1, Ordering::AcqRel) == 1 {
+            super::global_deallocate(chunk, chunk_layout());
+        }
+    }
+
+    pub fn allocate(layout: Layout) -> Option<NonNull<u8>> {
+        if !in_arena(layout) {
+            return super::global_allocate(layout);
+        }
+
+        let mut arena = ARENA.lock();
+        let mut offset = (arena.offset + layout.align() - 1) & !(layout.align() - 1);
+        let chunk = match arena.chunk {
+            Some(chunk) if offset + layout.size() <= CHUNK_SIZE => chunk,
+            current => {
+                let chunk = super::global_allocate(chunk_layout())?;
+                unsafe {
+                    std::ptr::write(chunk.as_ptr() as *mut ChunkHdr, ChunkHdr { live: AtomicUsize::new(1) });
+                    if let Some(current) = current {
+                        release_chunk(current);
+                    }
+                }
+                arena.chunk = Some(chunk);
+                offset = (std::mem::size_of::<ChunkHdr>() + layout.align() - 1) & !(layout.align() - 1);
+                chunk
+            }
+        };
+        chunk_header(chunk).live.fetch_add(1, Ordering::Relaxed);
+        arena.offset = offset + layout.size();
+        Some(unsafe { NonNull::new_unchecked(chunk.as_ptr().add(offset)) })
+    }
+
+    pub unsafe fn deallocate(ptr: NonNull<u8>, layout: Layout) {
+        if !in_arena(layout) {
+            return super::global_deallocate(ptr, layout);
+        }
+        let chunk = NonNull::new_unchecked((ptr.as_ptr() as usize & !(CHUNK_SIZE - 1)) as *mut u8);
+        release_chunk(chunk);
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::tests::test_lock;
+
+        #[test]
+        fn blocks_are_bump_allocated() {
+            let _lock = test_lock();
+
+            let layout = Layout::from_size_align(40, 8).unwrap();
+            let blocks: Vec<NonNull<u8>> = (0..64).map(|_| allocate(layout).unwrap()).collect();
+            let mut chunks = 1;
+            for w in blocks.windows(2) {
+                let (a, b) = (w[0].as_ptr() as usize, w[1].as_ptr() as usize);
+                if a & !(CHUNK_SIZE - 1) == b & !(CHUNK_SIZE - 1) {
+                    assert_eq!(b, a + 40);
+                } else {
+                    chunks += 1;
+                }
+            }
+            assert!(chunks <= 2);
+            for p in blocks {
+                unsafe { deallocate(p, layout) };
+            }
+        }
+    }
+}
+
+#[cfg(feature = "slab-allocator")]
+mod slab {
+    use super::*;
+
+    use parking_lot::Mutex;
+
+    /// Block sizes (including symbol header) served from free lists.
+    const SIZE_CLASSES: [usize; 8] = [32, 48, 64, 96, 128, 192, 256, 384];
+
+    const CLASS_ALIGN: usize = 16;
+
+    /// Maximal number of cached free blocks per size class.
+    const MAX_FREE_BLOCKS: usize = 1024;
+
+    struct FreeLists([Vec<NonNull<u8>>; SIZE_CLASSES.len()]);
+
+    unsafe impl Send for FreeLists {}
+
+    lazy_static!{
+        static ref FREE_LISTS: Mutex<FreeLists> = Mutex::new(FreeLists(Default::default()));
+    }
+
+    fn size_class(layout: Layout) -> Option<usize> {
+        if layout.align() > CLASS_ALIGN {
+            None
+        } else {
+            SIZE_CLASSES.iter().position(|&size| layout.size() <= size)
+        }
+    }
+
+    #[inline]
+    fn class_layout(class: usize) -> Layout {
+        unsafe { Layout::from_size_align_unchecked(SIZE_CLASSES[class], CLASS_ALIGN) }
+    }
+
+    pub fn allocate(layout: Layout) -> Option<NonNull<u8>> {
+        match size_class(layout) {
+            Some(class) => {
+                if let Some(p) = FREE_LISTS.lock().0[class].pop() {
+                    return Some(p);
+                }
+                super::base_allocate(class_layout(class))
+            }
+            None => super::base_allocate(layout),
+        }
+    }
+
+    pub unsafe fn deallocate(ptr: NonNull<u8>, layout: Layout) {
+        match size_class(layout) {
+            Some(class) => {
+                {
+                    let mut free_lists = FREE_LISTS.lock();
+                    let free_list = &mut free_lists.0[class];
+                    if free_list.len() < MAX_FREE_BLOCKS {
+                        free_list.push(ptr);
+                        return;
+                    }
+                }
+                super::base_deallocate(ptr, class_layout(class));
+            }
+            None => super::base_deallocate(ptr, layout),
+        }
+    }
+
+    pub fn allocated_size(layout: Layout) -> usize {
+        match size_class(layout) {
+            Some(class) => SIZE_CLASSES[class],
+            None => layout.size(),
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
+#[inline]
+fn global_allocate(layout: Layout) -> Option<NonNull<u8>> {
+    Global.allocate(layout).ok().map(|p| p.as_non_null_ptr())
+}
+
+#[cfg(feature = "nightly")]
+#[inline]
+unsafe fn global_deallocate(ptr: NonNull<u8>, layout: Layout) {
+    Global.deallocate(ptr, layout)
+}
+
+/// Symbol layouts always have non-zero size, since they include the symbol header.
+#[cfg(not(feature = "nightly"))]
+#[inline]
+fn global_allocate(layout: Layout) -> Option<NonNull<u8>> {
+    NonNull::new(unsafe { std::alloc::alloc(layout) })
+}
+
+#[cfg(not(feature = "nightly"))]
+#[inline]
+unsafe fn global_deallocate(ptr: NonNull<u8>, layout: Layout) {
+    std::alloc::dealloc(ptr.as_ptr(), layout)
+}
+
+/// Allocator backing the free lists of slab allocator, if enabled.
+#[inline]
+fn base_allocate(layout: Layout) -> Option<NonNull<u8>> {
+    #[cfg(feature = "arena-allocator")]
+    return arena::allocate(layout);
+
+    #[cfg(not(feature = "arena-allocator"))]
+    return global_allocate(layout);
+}
+
+#[inline]
+unsafe fn base_deallocate(ptr: NonNull<u8>, layout: Layout) {
+    #[cfg(feature = "arena-allocator")]
+    arena::deallocate(ptr, layout);
+
+    #[cfg(not(feature = "arena-allocator"))]
+    global_deallocate(ptr, layout);
+}
+
+#[inline]
+pub(crate) fn allocate(layout: Layout) -> Option<NonNull<u8>> {
+    #[cfg(feature = "test-util")]
+    {
+        if failure::should_fail() {
+            return None;
+        }
+    }
+
+    #[cfg(feature = "slab-allocator")]
+    return slab::allocate(layout);
+
+    #[cfg(not(feature = "slab-allocator"))]
+    return base_allocate(layout);
+}
+
+#[inline]
+pub(crate) unsafe fn deallocate(ptr: NonNull<u8>, layout: Layout) {
+    #[cfg(feature = "slab-allocator")]
+    slab::deallocate(ptr, layout);
+
+    #[cfg(not(feature = "slab-allocator"))]
+    base_deallocate(ptr, layout);
+}
+
+/// Returns number of bytes actually reserved for allocation with given layout.
+#[inline]
+pub(crate) fn allocated_size(layout: Layout) -> usize {
+    #[cfg(feature = "slab-allocator")]
+    return slab::allocated_size(layout);
+
+    #[cfg(not(feature = "slab-allocator"))]
+    return layout.size();
+}
+
+
+#[cfg(all(test, feature = "slab-allocator"))]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn freed_blocks_are_reused() {
+        let _lock = test_lock();
+
+        let p = {
+            let s = Symbol::from("short-lived");
+            s.0
+        };
+        // cached weak handle would keep the block allocated
+        #[cfg(feature = "thread-cache")]
+        clear_thread_cache();
+        let s = Symbol::from("another-one");
+
+        assert_eq!(s.0, p);
+    }
+}
+
+
+#[cfg(all(test, feature = "test-util"))]
+mod failure_tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn nth_allocation_fails() {
+        let _lock = test_lock();
+
+        fail_nth_allocation(2);
+        let s1 = Symbol::try_new("first");
+        let s2 = Symbol::try_new("second");
+        let s3 = Symbol::try_new("third");
+        let s4 = Symbol::try_new("first");
+
+        assert!(s1.is_ok());
+        assert_eq!(s2, Err(Error::Alloc(AllocError)));
+        assert!(s3.is_ok());
+        assert_eq!(s4, s1);
+        assert_eq!(Symbol::get("second"), None);
+    }
+}
@@ -0,0 +1,143 @@
+use super::{id, Symbol, SymbolHdr};
+
+use std::cmp::Ordering;
+use std::ops::Deref;
+
+/// `Copy` handle to a permanent symbol, represented by its 32-bit [`id`](Symbol::id).
+///
+/// Symbols referenced by atoms are never reclaimed, so atoms need no reference counting
+/// and can be freely copied, which makes them convenient in AST nodes and similar structures.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Atom(u32);
+
+impl Atom {
+    pub fn new<S: AsRef<str>>(value: S) -> Atom {
+        Atom::from(Symbol::permanent(value))
+    }
+
+    #[inline]
+    pub fn id(self) -> u32 {
+        self.0
+    }
+
+    /// Returns atom for a symbol id, if the symbol is alive and permanent.
+    pub fn from_id(id: u32) -> Option<Atom> {
+        Symbol::from_id(id).filter(Symbol::is_permanent).map(|_| Atom(id))
+    }
+
+    pub fn as_str(self) -> &'static str {
+        let p = id::lookup_id(self.0).expect("atom refers to a released symbol");
+        // permanent symbols are never deallocated
+        let hdr: &'static SymbolHdr = unsafe { &*(p.as_ptr() as *const SymbolHdr) };
+        hdr.as_ref()
+    }
+
+    pub fn symbol(self) -> Symbol {
+        Symbol::from_id(self.0).expect("atom refers to a released symbol")
+    }
+}
+
+impl From<Symbol> for Atom {
+    /// Makes the symbol permanent, see [`Symbol::make_permanent`].
+    fn from(s: Symbol) -> Self {
+        s.pin();
+        Atom(s.id())
+    }
+}
+
+impl<'a> From<&'a str> for Atom {
+    fn from(value: &'a str) -> Self {
+        Atom::new(value)
+    }
+}
+
+impl From<Atom> for Symbol {
+    fn from(a: Atom) -> Self {
+        a.symbol()
+    }
+}
+
+impl Deref for Atom {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Atom {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq<str> for Atom {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for Atom {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialOrd for Atom {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Atom {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.0 == other.0 {
+            Ordering::Equal
+        } else {
+            self.as_str().cmp(other.as_str())
+        }
+    }
+}
+
+impl std::fmt::Debug for Atom {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl std::fmt::Display for Atom {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn atoms_are_copy_and_permanent() {
+        let _lock = test_lock();
+
+        let a = Atom::new("atom");
+        let b = a;
+        assert_eq!(a, b);
+        assert_eq!(a, "atom");
+        assert_eq!(Atom::from(Symbol::new("atom")), a);
+        assert!(a.symbol().is_permanent());
+        assert_eq!(Atom::from_id(a.id()), Some(a));
+        assert!(Atom::new("other atom") > a);
+
+        let s = Symbol::new("not an atom");
+        assert_eq!(Atom::from_id(s.id()), None);
+        drop(s);
+
+        // permanent symbols outlive the test, remove them from the table to keep it clean for other tests
+        for v in ["atom", "other atom"].iter() {
+            let mut shard = SYMBOLS.shard(v).write();
+            std::mem::forget(shard.take(v));
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use super::{DefaultHashBuilder, Symbol};
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// Bidirectional one-to-one map between symbols and values.
+///
+/// Both directions are kept in sync, inserting a pair removes any previous pairs
+/// containing either the symbol or the value.
+pub struct SymbolBiMap<V: Hash + Eq> {
+    by_symbol: HashMap<Symbol, Arc<V>, DefaultHashBuilder>,
+    by_value: HashMap<Arc<V>, Symbol, DefaultHashBuilder>,
+}
+
+impl<V: Hash + Eq> SymbolBiMap<V> {
+    pub fn new() -> Self {
+        SymbolBiMap {
+            by_symbol: HashMap::default(),
+            by_value: HashMap::default(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        SymbolBiMap {
+            by_symbol: HashMap::with_capacity_and_hasher(capacity, Default::default()),
+            by_value: HashMap::with_capacity_and_hasher(capacity, Default::default()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_symbol.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_symbol.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.by_symbol.clear();
+        self.by_value.clear();
+    }
+
+    pub fn contains_symbol<Q>(&self, k: &Q) -> bool
+        where Q: ?Sized + Hash + Eq, Symbol: Borrow<Q>
+    {
+        self.by_symbol.contains_key(k)
+    }
+
+    pub fn contains_value(&self, v: &V) -> bool {
+        self.by_value.contains_key(v)
+    }
+
+    pub fn get_by_symbol<Q>(&self, k: &Q) -> Option<&V>
+        where Q: ?Sized + Hash + Eq, Symbol: Borrow<Q>
+    {
+        self.by_symbol.get(k).map(|v| v.as_ref())
+    }
+
+    pub fn get_by_value(&self, v: &V) -> Option<&Symbol> {
+        self.by_value.get(v)
+    }
+
+    /// Inserts a pair, returning the value previously associated with `k`
+    /// and the symbol previously associated with `v`.
+    pub fn insert(&mut self, k: Symbol, v: V) -> (Option<V>, Option<Symbol>) {
+        let old_value = self.remove_by_symbol(&k).map(|(_, v)| v);
+        let old_symbol = self.remove_by_value(&v).map(|(k, _)| k);
+        let v = Arc::new(v);
+        self.by_symbol.insert(k.clone(), v.clone());
+        self.by_value.insert(v, k);
+        (old_value, old_symbol)
+    }
+
+    pub fn remove_by_symbol<Q>(&mut self, k: &Q) -> Option<(Symbol, V)>
+        where Q: ?Sized + Hash + Eq, Symbol: Borrow<Q>
+    {
+        let (k, v) = self.by_symbol.remove_entry(k)?;
+        self.by_value.remove(&v);
+        Some((k, Self::unwrap_value(v)))
+    }
+
+    pub fn remove_by_value(&mut self, v: &V) -> Option<(Symbol, V)> {
+        let (v, k) = self.by_value.remove_entry(v)?;
+        self.by_symbol.remove(&k);
+        Some((k, Self::unwrap_value(v)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Symbol, &V)> {
+        self.by_symbol.iter().map(|(k, v)| (k, v.as_ref()))
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = &Symbol> {
+        self.by_symbol.keys()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.by_value.keys().map(|v| v.as_ref())
+    }
+
+    fn unwrap_value(v: Arc<V>) -> V {
+        // value is shared only between both directions, and the other one has been already removed
+        match Arc::try_unwrap(v) {
+            Ok(v) => v,
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+impl<V: Hash + Eq> Default for SymbolBiMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Hash + Eq + std::fmt::Debug> std::fmt::Debug for SymbolBiMap<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn lookups_in_both_directions() {
+        let _lock = test_lock();
+
+        let mut m = SymbolBiMap::new();
+        m.insert("one".into(), 1);
+        m.insert("two".into(), 2);
+
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.get_by_symbol("one"), Some(&1));
+        assert_eq!(m.get_by_value(&2).map(|s| s.as_str()), Some("two"));
+        assert!(m.contains_symbol("two"));
+        assert!(!m.contains_value(&3));
+    }
+
+    #[test]
+    fn insert_replaces_pairs_on_both_sides() {
+        let _lock = test_lock();
+
+        let mut m = SymbolBiMap::new();
+        m.insert("one".into(), 1);
+        m.insert("two".into(), 2);
+
+        let (old_value, old_symbol) = m.insert("one".into(), 2);
+
+        assert_eq!(old_value, Some(1));
+        assert_eq!(old_symbol, Some(Symbol::from("two")));
+        assert_eq!(m.len(), 1);
+        assert_eq!(m.get_by_value(&1), None);
+        assert_eq!(m.get_by_symbol("two"), None);
+        assert_eq!(m.get_by_value(&2), Some(&Symbol::from("one")));
+    }
+
+    #[test]
+    fn remove_keeps_directions_in_sync() {
+        let _lock = test_lock();
+
+        let mut m = SymbolBiMap::new();
+        m.insert("one".into(), 1);
+        m.insert("two".into(), 2);
+
+        assert_eq!(m.remove_by_value(&1).map(|(k, v)| (k.to_string(), v)), Some(("one".to_string(), 1)));
+        assert_eq!(m.remove_by_symbol("two").map(|(_, v)| v), Some(2));
+        assert!(m.is_empty());
+        assert_eq!(m.values().count(), 0);
+    }
+}
@@ -0,0 +1,211 @@
+use super::Symbol;
+
+use std::collections::BTreeMap;
+use std::collections::btree_map;
+use std::iter::FromIterator;
+use std::ops::{Bound, RangeBounds};
+
+/// Map with symbol keys kept sorted by their strings, supporting range queries.
+///
+/// Unlike [`SymbolMap`](crate::SymbolMap), which preserves insertion order, iteration
+/// and serialization follow lexicographic order of keys.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SymbolBTreeMap<V> {
+    map: BTreeMap<Symbol, V>,
+}
+
+impl<V> SymbolBTreeMap<V> {
+    pub fn new() -> Self {
+        SymbolBTreeMap {
+            map: BTreeMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    pub fn contains_key<Q: AsRef<str> + ?Sized>(&self, k: &Q) -> bool {
+        self.map.contains_key(k.as_ref())
+    }
+
+    pub fn get<Q: AsRef<str> + ?Sized>(&self, k: &Q) -> Option<&V> {
+        self.map.get(k.as_ref())
+    }
+
+    pub fn get_key_value<Q: AsRef<str> + ?Sized>(&self, k: &Q) -> Option<(&Symbol, &V)> {
+        self.map.get_key_value(k.as_ref())
+    }
+
+    pub fn get_mut<Q: AsRef<str> + ?Sized>(&mut self, k: &Q) -> Option<&mut V> {
+        self.map.get_mut(k.as_ref())
+    }
+
+    pub fn insert(&mut self, k: Symbol, v: V) -> Option<V> {
+        self.map.insert(k, v)
+    }
+
+    pub fn remove<Q: AsRef<str> + ?Sized>(&mut self, k: &Q) -> Option<V> {
+        self.map.remove(k.as_ref())
+    }
+
+    pub fn entry(&mut self, k: Symbol) -> btree_map::Entry<'_, Symbol, V> {
+        self.map.entry(k)
+    }
+
+    pub fn first_key_value(&self) -> Option<(&Symbol, &V)> {
+        self.map.iter().next()
+    }
+
+    pub fn last_key_value(&self) -> Option<(&Symbol, &V)> {
+        self.map.iter().next_back()
+    }
+
+    /// Returns entries with keys in `range`, e.g. `map.range("a".."b")`.
+    pub fn range<Q, R>(&self, range: R) -> btree_map::Range<'_, Symbol, V>
+        where Q: AsRef<str>, R: RangeBounds<Q>
+    {
+        self.map.range::<str, _>(str_bounds(&range))
+    }
+
+    pub fn range_mut<Q, R>(&mut self, range: R) -> btree_map::RangeMut<'_, Symbol, V>
+        where Q: AsRef<str>, R: RangeBounds<Q>
+    {
+        self.map.range_mut::<str, _>(str_bounds(&range))
+    }
+
+    /// Returns entries with keys starting with `prefix`, in order.
+    pub fn range_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a Symbol, &'a V)> {
+        self.map.range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(move |(k, _)| k.starts_with(prefix))
+    }
+
+    pub fn iter(&self) -> btree_map::Iter<'_, Symbol, V> {
+        self.map.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> btree_map::IterMut<'_, Symbol, V> {
+        self.map.iter_mut()
+    }
+
+    pub fn keys(&self) -> btree_map::Keys<'_, Symbol, V> {
+        self.map.keys()
+    }
+
+    pub fn values(&self) -> btree_map::Values<'_, Symbol, V> {
+        self.map.values()
+    }
+
+    pub fn values_mut(&mut self) -> btree_map::ValuesMut<'_, Symbol, V> {
+        self.map.values_mut()
+    }
+}
+
+fn str_bounds<'a, Q: AsRef<str> + 'a, R: RangeBounds<Q>>(range: &'a R) -> (Bound<&'a str>, Bound<&'a str>) {
+    fn map<'a, Q: AsRef<str> + 'a>(b: Bound<&'a Q>) -> Bound<&'a str> {
+        match b {
+            Bound::Included(q) => Bound::Included(q.as_ref()),
+            Bound::Excluded(q) => Bound::Excluded(q.as_ref()),
+            Bound::Unbounded => Bound::Unbounded,
+        }
+    }
+    (map(range.start_bound()), map(range.end_bound()))
+}
+
+impl<V> Default for SymbolBTreeMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> FromIterator<(Symbol, V)> for SymbolBTreeMap<V> {
+    fn from_iter<I: IntoIterator<Item = (Symbol, V)>>(iter: I) -> Self {
+        SymbolBTreeMap {
+            map: BTreeMap::from_iter(iter),
+        }
+    }
+}
+
+impl<V> Extend<(Symbol, V)> for SymbolBTreeMap<V> {
+    fn extend<I: IntoIterator<Item = (Symbol, V)>>(&mut self, iter: I) {
+        self.map.extend(iter)
+    }
+}
+
+impl<V> IntoIterator for SymbolBTreeMap<V> {
+    type Item = (Symbol, V);
+    type IntoIter = btree_map::IntoIter<Symbol, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter()
+    }
+}
+
+impl<'a, V> IntoIterator for &'a SymbolBTreeMap<V> {
+    type Item = (&'a Symbol, &'a V);
+    type IntoIter = btree_map::Iter<'a, Symbol, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.iter()
+    }
+}
+
+impl<V: std::fmt::Debug> std::fmt::Debug for SymbolBTreeMap<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.map.iter()).finish()
+    }
+}
+
+/// Serializes entries sorted by key.
+impl<V: serde::Serialize> serde::Serialize for SymbolBTreeMap<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+        serializer.collect_map(self.map.iter())
+    }
+}
+
+impl<'de, V: serde::Deserialize<'de>> serde::Deserialize<'de> for SymbolBTreeMap<V> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: serde::Deserializer<'de> {
+        Ok(SymbolBTreeMap {
+            map: BTreeMap::deserialize(deserializer)?,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn keys_are_sorted_and_range_queryable() {
+        let _lock = test_lock();
+
+        let mut m: SymbolBTreeMap<i32> = ["beta", "alpha", "gamma", "alphabet"].iter()
+            .enumerate()
+            .map(|(i, &k)| (Symbol::new(k), i as i32))
+            .collect();
+        assert_eq!(m.keys().map(|k| k.as_str()).collect::<Vec<_>>(), ["alpha", "alphabet", "beta", "gamma"]);
+        assert_eq!(m.get("beta"), Some(&0));
+
+        let range: Vec<&str> = m.range("alpha".."beta").map(|(k, _)| k.as_str()).collect();
+        assert_eq!(range, ["alpha", "alphabet"]);
+        let range: Vec<&str> = m.range(Symbol::new("b")..).map(|(k, _)| k.as_str()).collect();
+        assert_eq!(range, ["beta", "gamma"]);
+        let prefixed: Vec<&str> = m.range_prefix("alph").map(|(k, _)| k.as_str()).collect();
+        assert_eq!(prefixed, ["alpha", "alphabet"]);
+
+        m.range_mut::<&str, _>(..).for_each(|(_, v)| *v += 10);
+        assert_eq!(m.remove("gamma"), Some(12));
+        assert_eq!(m.last_key_value(), Some((&Symbol::new("beta"), &10)));
+        assert_eq!(serde_json::to_string(&m).unwrap(), r#"{"alpha":11,"alphabet":13,"beta":10}"#);
+    }
+}
@@ -0,0 +1,82 @@
+/// Capacity of the inline part of [`StrBuf`], large enough for most identifiers.
+const INLINE_CAPACITY: usize = 64;
+
+/// String buffer kept on the stack until it outgrows the inline capacity, used to build
+/// strings which are only needed for interning.
+pub(crate) struct StrBuf {
+    inline: [u8; INLINE_CAPACITY],
+    len: usize,
+    heap: Option<String>,
+}
+
+impl StrBuf {
+    pub(crate) fn new() -> StrBuf {
+        StrBuf {
+            inline: [0; INLINE_CAPACITY],
+            len: 0,
+            heap: None,
+        }
+    }
+
+    /// Creates buffer for a string of known length, allocating only when it does not fit inline.
+    pub(crate) fn with_capacity(capacity: usize) -> StrBuf {
+        let mut buf = StrBuf::new();
+        if capacity > INLINE_CAPACITY {
+            buf.heap = Some(String::with_capacity(capacity));
+        }
+        buf
+    }
+
+    pub(crate) fn push_str(&mut self, s: &str) {
+        if let Some(ref mut heap) = self.heap {
+            heap.push_str(s);
+        } else if self.len + s.len() <= INLINE_CAPACITY {
+            self.inline[self.len..self.len + s.len()].copy_from_slice(s.as_bytes());
+            self.len += s.len();
+        } else {
+            let mut heap = String::with_capacity((self.len + s.len()).max(INLINE_CAPACITY * 2));
+            heap.push_str(self.as_str());
+            heap.push_str(s);
+            self.heap = Some(heap);
+        }
+    }
+
+    pub(crate) fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        match self.heap {
+            Some(ref heap) => heap,
+            // inline bytes are only ever written from whole `str` slices
+            None => unsafe { std::str::from_utf8_unchecked(&self.inline[..self.len]) },
+        }
+    }
+}
+
+impl std::fmt::Write for StrBuf {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spills_to_heap_when_full() {
+        let mut buf = StrBuf::new();
+        buf.push_str("ab");
+        buf.push('\u{1F600}');
+        assert_eq!(buf.as_str(), "ab\u{1F600}");
+        assert!(buf.heap.is_none());
+
+        let long = "x".repeat(INLINE_CAPACITY);
+        buf.push_str(&long);
+        assert_eq!(buf.as_str(), format!("ab\u{1F600}{}", long));
+        assert!(buf.heap.is_some());
+    }
+}
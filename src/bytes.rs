@@ -0,0 +1,165 @@
+use super::DefaultHashBuilder;
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+lazy_static! {
+    static ref BYTE_SYMBOLS: RwLock<HashSet<Arc<[u8]>, DefaultHashBuilder>> = RwLock::new(HashSet::default());
+}
+
+/// Interned byte string, without UTF-8 requirement.
+///
+/// Byte symbols have their own global table, separate from [`Symbol`](crate::Symbol)s, and are
+/// compared by pointer. Byte strings are removed from the table when the last handle is dropped.
+#[derive(Clone)]
+pub struct ByteSymbol(Arc<[u8]>);
+
+impl ByteSymbol {
+    pub fn new<B: AsRef<[u8]>>(value: B) -> ByteSymbol {
+        let value = value.as_ref();
+        if let Some(s) = ByteSymbol::get(value) {
+            return s;
+        }
+
+        let mut table = BYTE_SYMBOLS.write();
+        if let Some(s) = table.get(value) {
+            return ByteSymbol(s.clone());
+        }
+        let s: Arc<[u8]> = value.into();
+        table.insert(s.clone());
+        ByteSymbol(s)
+    }
+
+    /// Returns byte symbol for `value`, if it is already interned.
+    pub fn get<B: AsRef<[u8]>>(value: B) -> Option<ByteSymbol> {
+        BYTE_SYMBOLS.read().get(value.as_ref()).map(|s| ByteSymbol(s.clone()))
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the byte string as `str`, if it is valid UTF-8.
+    pub fn to_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.0).ok()
+    }
+}
+
+impl Drop for ByteSymbol {
+    fn drop(&mut self) {
+        // the table holds the other reference; new handles can only be created from
+        // the table under lock, or from other handles, which would increase the count
+        if Arc::strong_count(&self.0) == 2 {
+            let mut table = BYTE_SYMBOLS.write();
+            if Arc::strong_count(&self.0) == 2 {
+                table.remove(&self.0[..]);
+            }
+        }
+    }
+}
+
+impl Deref for ByteSymbol {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for ByteSymbol {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<'a> From<&'a [u8]> for ByteSymbol {
+    fn from(value: &'a [u8]) -> Self {
+        ByteSymbol::new(value)
+    }
+}
+
+impl From<Vec<u8>> for ByteSymbol {
+    fn from(value: Vec<u8>) -> Self {
+        ByteSymbol::new(value)
+    }
+}
+
+impl PartialEq for ByteSymbol {
+    #[inline]
+    fn eq(&self, other: &ByteSymbol) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ByteSymbol {}
+
+impl PartialEq<[u8]> for ByteSymbol {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_bytes() == other
+    }
+}
+
+impl<'a> PartialEq<&'a [u8]> for ByteSymbol {
+    fn eq(&self, other: &&'a [u8]) -> bool {
+        self.as_bytes() == *other
+    }
+}
+
+impl PartialOrd for ByteSymbol {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ByteSymbol {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.eq(other) {
+            Ordering::Equal
+        } else {
+            self.as_bytes().cmp(other.as_bytes())
+        }
+    }
+}
+
+impl Hash for ByteSymbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state)
+    }
+}
+
+impl std::fmt::Debug for ByteSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "b\"{}\"", self.0.escape_ascii())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn byte_symbols_are_interned_and_released() {
+        let a = ByteSymbol::new(b"\xff\x00tag");
+        assert_eq!(ByteSymbol::from(b"\xff\x00tag".to_vec()), a);
+        assert_eq!(a, &b"\xff\x00tag"[..]);
+        assert_eq!(a.to_str(), None);
+        assert_eq!(ByteSymbol::new("text").to_str(), Some("text"));
+        assert_ne!(ByteSymbol::new(b"other"), a);
+        assert_eq!(format!("{:?}", a), r#"b"\xff\x00tag""#);
+
+        let b = a.clone();
+        drop(a);
+        assert_eq!(ByteSymbol::get(b"\xff\x00tag"), Some(b.clone()));
+        drop(b);
+        assert_eq!(ByteSymbol::get(b"\xff\x00tag"), None);
+    }
+}
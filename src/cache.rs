@@ -0,0 +1,69 @@
+use super::{Symbol, WeakSymbol};
+
+use std::cell::RefCell;
+
+/// Number of slots in the per-thread cache, must be a power of two.
+const CACHE_SIZE: usize = 256;
+
+thread_local! {
+    /// Direct-mapped cache of symbols recently interned by the current thread. Entries are weak,
+    /// so cached symbols are still reclaimed when the last strong handle is dropped.
+    static CACHE: RefCell<Vec<Option<WeakSymbol>>> = RefCell::new(vec![None; CACHE_SIZE]);
+}
+
+#[inline]
+fn slot(hash: u64) -> usize {
+    hash as usize & (CACHE_SIZE - 1)
+}
+
+/// Looks up `value` with precomputed [`BuildSymbolHasher`](crate::BuildSymbolHasher) hash
+/// in the cache of the current thread.
+#[inline]
+pub(crate) fn get(value: &str, hash: u64) -> Option<Symbol> {
+    CACHE.try_with(|c| {
+        match c.borrow()[slot(hash)] {
+            Some(ref w) if w.as_str() == value => w.upgrade(),
+            _ => None,
+        }
+    }).ok().flatten()
+}
+
+#[inline]
+pub(crate) fn insert(hash: u64, s: &Symbol) {
+    // cache might be already destroyed if called during thread shutdown
+    let _ = CACHE.try_with(|c| c.borrow_mut()[slot(hash)] = Some(s.downgrade()));
+}
+
+/// Clears symbol cache of the current thread, releasing memory of cached symbols
+/// which were already destroyed.
+pub fn clear_thread_cache() {
+    let _ = CACHE.try_with(|c| c.borrow_mut().iter_mut().for_each(|e| *e = None));
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    use std::hash::BuildHasher;
+
+    #[test]
+    fn cached_symbols_are_reclaimed() {
+        let _lock = test_lock();
+
+        let s = Symbol::new("cached");
+        let hash = BuildSymbolHasher::default().hash_one("cached");
+        assert_eq!(super::get("cached", hash), Some(s.clone()));
+        assert_eq!(Symbol::new("cached"), s);
+
+        drop(s);
+        assert_eq!(super::get("cached", hash), None);
+        assert_eq!(Symbol::get("cached"), None);
+
+        let s = Symbol::new("cached");
+        clear_thread_cache();
+        assert_eq!(super::get("cached", hash), None);
+        assert_eq!(Symbol::new("cached"), s);
+    }
+}
@@ -0,0 +1,277 @@
+use super::{Symbol, SymbolMap};
+use super::fold::fold;
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+/// Ordered map with case-insensitive symbol keys.
+///
+/// Keys are compared after Unicode case folding, while original spelling of the most recently
+/// inserted key is preserved for iteration.
+pub struct CaselessSymbolMap<V> {
+    map: SymbolMap<(Symbol, V)>,
+}
+
+impl<V> CaselessSymbolMap<V> {
+    pub fn new() -> Self {
+        CaselessSymbolMap {
+            map: SymbolMap::new(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        CaselessSymbolMap {
+            map: SymbolMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.len() == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    pub fn contains_key<Q: AsRef<str> + ?Sized>(&self, k: &Q) -> bool {
+        self.map.get(fold(k.as_ref()).as_ref()).is_some()
+    }
+
+    pub fn get<Q: AsRef<str> + ?Sized>(&self, k: &Q) -> Option<&V> {
+        self.map.get(fold(k.as_ref()).as_ref()).map(|e| &e.1)
+    }
+
+    /// Returns the key with its original spelling, together with the value.
+    pub fn get_key_value<Q: AsRef<str> + ?Sized>(&self, k: &Q) -> Option<(&Symbol, &V)> {
+        self.map.get(fold(k.as_ref()).as_ref()).map(|e| (&e.0, &e.1))
+    }
+
+    pub fn get_mut<Q: AsRef<str> + ?Sized>(&mut self, k: &Q) -> Option<&mut V> {
+        self.map.get_mut(fold(k.as_ref()).as_ref()).map(|e| &mut e.1)
+    }
+
+    /// Inserts value, replacing both the value and key spelling of an existing entry.
+    pub fn insert(&mut self, k: Symbol, v: V) -> Option<V> {
+        self.map.insert(fold_symbol(&k), (k, v)).map(|e| e.1)
+    }
+
+    pub fn remove<Q: AsRef<str> + ?Sized>(&mut self, k: &Q) -> Option<V> {
+        self.map.remove(fold(k.as_ref()).as_ref()).map(|e| e.1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Symbol, &V)> {
+        self.map.values().map(|e| (&e.0, &e.1))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Symbol> {
+        self.map.values().map(|e| &e.0)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.map.values().map(|e| &e.1)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.map.values_mut().map(|e| &mut e.1)
+    }
+}
+
+impl<V> Default for CaselessSymbolMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: std::fmt::Debug> std::fmt::Debug for CaselessSymbolMap<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Case-insensitive symbol, remembering the original spelling.
+///
+/// Equality, ordering and hashing use the case-folded form, which is interned as well,
+/// so comparisons are as cheap as for [`Symbol`].
+#[derive(Clone)]
+pub struct CiSymbol {
+    original: Symbol,
+    folded: Symbol,
+}
+
+impl CiSymbol {
+    pub fn new<S: AsRef<str>>(value: S) -> CiSymbol {
+        CiSymbol::from(Symbol::new(value))
+    }
+
+    /// Returns the original spelling.
+    pub fn original(&self) -> &Symbol {
+        &self.original
+    }
+
+    /// Returns the case-folded canonical form.
+    pub fn folded(&self) -> &Symbol {
+        &self.folded
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.original.as_str()
+    }
+}
+
+impl From<Symbol> for CiSymbol {
+    fn from(original: Symbol) -> Self {
+        let folded = fold_symbol(&original);
+        CiSymbol { original, folded }
+    }
+}
+
+impl<'a> From<&'a str> for CiSymbol {
+    fn from(value: &'a str) -> Self {
+        CiSymbol::new(value)
+    }
+}
+
+impl From<CiSymbol> for Symbol {
+    fn from(s: CiSymbol) -> Self {
+        s.original
+    }
+}
+
+impl AsRef<str> for CiSymbol {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for CiSymbol {
+    fn eq(&self, other: &CiSymbol) -> bool {
+        self.folded == other.folded
+    }
+}
+
+impl Eq for CiSymbol {}
+
+impl PartialEq<str> for CiSymbol {
+    fn eq(&self, other: &str) -> bool {
+        self.folded.as_str() == fold(other)
+    }
+}
+
+impl<'a> PartialEq<&'a str> for CiSymbol {
+    fn eq(&self, other: &&'a str) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialOrd for CiSymbol {
+    fn partial_cmp(&self, other: &CiSymbol) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CiSymbol {
+    fn cmp(&self, other: &CiSymbol) -> Ordering {
+        self.folded.cmp(&other.folded)
+    }
+}
+
+impl Hash for CiSymbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.folded.hash(state)
+    }
+}
+
+impl std::fmt::Debug for CiSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.original, f)
+    }
+}
+
+impl std::fmt::Display for CiSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.original, f)
+    }
+}
+
+fn fold_symbol(s: &Symbol) -> Symbol {
+    match fold(s) {
+        Cow::Borrowed(_) => s.clone(),
+        Cow::Owned(f) => Symbol::new(f),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn lookups_ignore_case() {
+        let _lock = test_lock();
+
+        let mut m = CaselessSymbolMap::new();
+        m.insert("Content-Type".into(), 1);
+        m.insert("ÉTAT".into(), 2);
+
+        assert_eq!(m.get("content-type"), Some(&1));
+        assert_eq!(m.get("CONTENT-TYPE"), Some(&1));
+        assert_eq!(m.get("état"), Some(&2));
+        assert!(!m.contains_key("content-length"));
+        assert_eq!(m.get_key_value("content-TYPE").map(|(k, _)| k.as_ref()), Some("Content-Type"));
+    }
+
+    #[test]
+    fn insert_replaces_spelling_and_value() {
+        let _lock = test_lock();
+
+        let mut m = CaselessSymbolMap::new();
+        m.insert("Accept".into(), 1);
+        m.insert("Host".into(), 2);
+        assert_eq!(m.insert("ACCEPT".into(), 3), Some(1));
+
+        let entries: Vec<_> = m.iter().map(|(k, &v)| (k.to_string(), v)).collect();
+        assert_eq!(entries, [("ACCEPT".to_string(), 3), ("Host".to_string(), 2)]);
+
+        assert_eq!(m.remove("host"), Some(2));
+        assert_eq!(m.len(), 1);
+    }
+
+    #[test]
+    fn ci_symbol_compares_folded_form() {
+        let _lock = test_lock();
+
+        let a = CiSymbol::new("Content-Type");
+        let b = CiSymbol::from("CONTENT-type");
+        assert_eq!(a, b);
+        assert_eq!(a.as_str(), "Content-Type");
+        assert_eq!(b.original(), "CONTENT-type");
+        assert_eq!(a.folded(), "content-type");
+        assert_eq!(a, "content-TYPE");
+        assert_ne!(a, CiSymbol::new("Content-Length"));
+        assert!(CiSymbol::new("B") > CiSymbol::new("a"));
+
+        let mut set = std::collections::HashSet::new();
+        set.insert(a.clone());
+        assert!(set.contains(&b));
+        assert_eq!(format!("{} {:?}", a, b), "Content-Type \"CONTENT-type\"");
+    }
+
+    #[test]
+    fn keys_use_full_case_folding() {
+        let _lock = test_lock();
+
+        let mut m = CaselessSymbolMap::new();
+        m.insert("Straße".into(), 1);
+        assert_eq!(m.get("STRASSE"), Some(&1));
+        assert_eq!(m.get("strasse"), Some(&1));
+        assert_eq!(CiSymbol::new("Straße"), CiSymbol::new("STRASSE"));
+        assert_eq!(CiSymbol::new("STRASSE").folded(), "strasse");
+        assert_ne!(CiSymbol::new("ı"), CiSymbol::new("I"));
+    }
+}
@@ -0,0 +1,204 @@
+use super::phf::build_table;
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Generates Rust code for a fixed list of symbols, for use in build scripts.
+///
+/// `word_list` contains one word per line, empty lines are skipped and duplicates are ignored.
+/// Generated code declares:
+/// * `WORDS` array with all words,
+/// * index constant for every word, named after the word in upper case
+///   (non-alphanumeric characters are replaced with `_`, and a numeric suffix is appended
+///   if the name is already taken),
+/// * `symbols()` returning slice of symbols, interned once on first use and kept alive
+///   for the rest of the program,
+/// * `symbol(index)` returning symbol for an index constant,
+/// * `lookup(&str)` returning index of a word from the list.
+///
+/// Typical usage in `build.rs`:
+///
+/// ```no_run
+/// let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("keywords.rs");
+/// kg_symbol::generate_symbols_file("keywords.txt", out).unwrap();
+/// ```
+///
+/// and then in the crate:
+/// `#[allow(dead_code)] mod keywords { include!(concat!(env!("OUT_DIR"), "/keywords.rs")); }`.
+pub fn generate_symbols<W: Write>(word_list: &str, mut out: W) -> io::Result<()> {
+    let words = parse_word_list(word_list);
+
+    writeln!(out, "// Generated by kg-symbol, do not edit.")?;
+    writeln!(out)?;
+    writeln!(out, "pub const WORDS: [&str; {}] = [", words.len())?;
+    for w in words.iter() {
+        writeln!(out, "    {:?},", w)?;
+    }
+    writeln!(out, "];")?;
+    writeln!(out)?;
+
+    // names of other generated items are reserved
+    let mut names: HashSet<String> = ["WORDS", "SYMBOLS"].iter().map(|n| n.to_string()).collect();
+    for (i, w) in words.iter().enumerate() {
+        let base = const_name(w);
+        let mut name = base.clone();
+        let mut suffix = i;
+        while !names.insert(name.clone()) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        writeln!(out, "pub const {}: usize = {};", name, i)?;
+    }
+    writeln!(out)?;
+
+    writeln!(out, "pub fn symbols() -> &'static [::kg_symbol::Symbol] {{")?;
+    writeln!(out, "    static SYMBOLS: ::std::sync::OnceLock<::std::vec::Vec<::kg_symbol::Symbol>> = ::std::sync::OnceLock::new();")?;
+    writeln!(out, "    SYMBOLS.get_or_init(|| WORDS.iter().map(::kg_symbol::Symbol::new).collect())")?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(out, "pub fn symbol(index: usize) -> ::kg_symbol::Symbol {{")?;
+    writeln!(out, "    symbols()[index].clone()")?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(out, "pub fn lookup(value: &str) -> ::std::option::Option<usize> {{")?;
+    writeln!(out, "    match value {{")?;
+    for (i, w) in words.iter().enumerate() {
+        writeln!(out, "        {:?} => ::std::option::Option::Some({}),", w, i)?;
+    }
+    writeln!(out, "        _ => ::std::option::Option::None,")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+/// Reads word list from `input` file and writes generated code to `output` file.
+/// See [`generate_symbols`] for details.
+pub fn generate_symbols_file<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q) -> io::Result<()> {
+    let word_list = std::fs::read_to_string(input)?;
+    let mut code = Vec::new();
+    generate_symbols(&word_list, &mut code)?;
+    std::fs::write(output, code)
+}
+
+/// Generates Rust code for a perfect hash table of static symbols, for use in build scripts.
+///
+/// `word_list` has the same format as for [`generate_symbols`]. Perfect hash is computed
+/// at build time, generated code declares:
+/// * `SEED` and `DISPLACEMENTS` of the perfect hash table,
+/// * `WORDS` array with all words, in slot order,
+/// * `install()` installing the table with [`set_static_symbols`](crate::set_static_symbols),
+///   to be called at startup, before interning any of the words.
+///
+/// Once installed, [`Symbol::new`](crate::Symbol::new) resolves listed words without locking
+/// the global symbol table.
+pub fn generate_static_symbols<W: Write>(word_list: &str, mut out: W) -> io::Result<()> {
+    let words = parse_word_list(word_list);
+    let (seed, displacements, slots) = build_table(&words);
+
+    writeln!(out, "// Generated by kg-symbol, do not edit.")?;
+    writeln!(out)?;
+    writeln!(out, "pub const SEED: u64 = {};", seed)?;
+    writeln!(out)?;
+    writeln!(out, "pub static DISPLACEMENTS: [(u32, u32); {}] = [", displacements.len())?;
+    for (d1, d2) in displacements.iter() {
+        writeln!(out, "    ({}, {}),", d1, d2)?;
+    }
+    writeln!(out, "];")?;
+    writeln!(out)?;
+    writeln!(out, "pub static WORDS: [&str; {}] = [", slots.len())?;
+    for &k in slots.iter() {
+        writeln!(out, "    {:?},", words[k])?;
+    }
+    writeln!(out, "];")?;
+    writeln!(out)?;
+    writeln!(out, "pub fn install() -> bool {{")?;
+    writeln!(out, "    ::kg_symbol::set_static_symbols(::kg_symbol::PerfectHashSymbolSet::from_parts(SEED, &DISPLACEMENTS, &WORDS))")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+/// Reads word list from `input` file and writes generated code to `output` file.
+/// See [`generate_static_symbols`] for details.
+pub fn generate_static_symbols_file<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q) -> io::Result<()> {
+    let word_list = std::fs::read_to_string(input)?;
+    let mut code = Vec::new();
+    generate_static_symbols(&word_list, &mut code)?;
+    std::fs::write(output, code)
+}
+
+fn parse_word_list(word_list: &str) -> Vec<&str> {
+    let mut seen = HashSet::new();
+    word_list.lines()
+        .map(|w| w.trim_end_matches('\r'))
+        .filter(|w| !w.is_empty() && seen.insert(*w))
+        .collect()
+}
+
+fn const_name(word: &str) -> String {
+    let mut name: String = word.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    if name.chars().all(|c| c == '_') {
+        name.push_str("WORD");
+    }
+    name
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn const_names() {
+        assert_eq!(const_name("while"), "WHILE");
+        assert_eq!(const_name("content-type"), "CONTENT_TYPE");
+        assert_eq!(const_name("3d"), "_3D");
+        assert_eq!(const_name("+"), "_WORD");
+    }
+
+    #[test]
+    fn generated_code() {
+        let mut code = Vec::new();
+        generate_symbols("if\nelse\r\n\nif\nIF\n\"q\"\n", &mut code).unwrap();
+        let code = String::from_utf8(code).unwrap();
+
+        assert!(code.contains("pub const WORDS: [&str; 4] = [\n    \"if\",\n    \"else\",\n    \"IF\",\n    \"\\\"q\\\"\",\n];"));
+        assert!(code.contains("pub const IF: usize = 0;"));
+        assert!(code.contains("pub const ELSE: usize = 1;"));
+        assert!(code.contains("pub const IF_2: usize = 2;"));
+        assert!(code.contains("pub const _Q_: usize = 3;"));
+        assert!(code.contains("        \"else\" => ::std::option::Option::Some(1),"));
+    }
+
+    #[test]
+    fn generated_names_are_unique() {
+        let mut code = Vec::new();
+        generate_symbols("a_2\na\nA\nwords\nsymbols\n", &mut code).unwrap();
+        let code = String::from_utf8(code).unwrap();
+
+        assert!(code.contains("pub const A_2: usize = 0;"));
+        assert!(code.contains("pub const A: usize = 1;"));
+        assert!(code.contains("pub const A_3: usize = 2;"));
+        assert!(code.contains("pub const WORDS_3: usize = 3;"));
+        assert!(code.contains("pub const SYMBOLS_4: usize = 4;"));
+        assert_eq!(code.matches("pub const WORDS:").count(), 1);
+    }
+
+    #[test]
+    fn generated_static_code() {
+        let mut code = Vec::new();
+        generate_static_symbols("if\nelse\nif\n", &mut code).unwrap();
+        let code = String::from_utf8(code).unwrap();
+
+        assert!(code.contains("pub const SEED: u64 = "));
+        assert!(code.contains("pub static DISPLACEMENTS: [(u32, u32); 1] = ["));
+        assert!(code.contains("pub static WORDS: [&str; 2] = ["));
+        assert!(code.contains("    \"else\",\n"));
+        assert!(code.contains("pub fn install() -> bool {"));
+    }
+}
@@ -0,0 +1,157 @@
+use super::Symbol;
+
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+
+/// Locale-aware string comparison, e.g. implemented with an ICU collator.
+pub trait Collator {
+    fn compare(&self, a: &str, b: &str) -> Ordering;
+}
+
+impl<F> Collator for F where F: Fn(&str, &str) -> Ordering {
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        self(a, b)
+    }
+}
+
+impl Symbol {
+    /// Compares symbols using `collator`, instead of the byte ordering of `Ord`.
+    pub fn cmp_collated<C: Collator + ?Sized>(&self, other: &Symbol, collator: &C) -> Ordering {
+        if self == other {
+            Ordering::Equal
+        } else {
+            collator.compare(self.as_str(), other.as_str())
+        }
+    }
+}
+
+/// Collator type with a default instance, used by [`CollatedSymbol`].
+pub trait DefaultCollator: Collator + 'static {
+    fn collator() -> &'static Self;
+}
+
+/// Symbol ordered with collator `C`, for sorting symbols in a human-sensible order.
+///
+/// Equality is still symbol identity, while ordering uses the collator, so the collator should
+/// only consider different strings equal if such ties are acceptable for sorting.
+pub struct CollatedSymbol<C: DefaultCollator> {
+    symbol: Symbol,
+    _collator: PhantomData<fn() -> C>,
+}
+
+impl<C: DefaultCollator> CollatedSymbol<C> {
+    pub fn new(symbol: Symbol) -> Self {
+        CollatedSymbol {
+            symbol,
+            _collator: PhantomData,
+        }
+    }
+
+    pub fn symbol(&self) -> &Symbol {
+        &self.symbol
+    }
+
+    pub fn into_symbol(self) -> Symbol {
+        self.symbol
+    }
+}
+
+impl<C: DefaultCollator> From<Symbol> for CollatedSymbol<C> {
+    fn from(symbol: Symbol) -> Self {
+        CollatedSymbol::new(symbol)
+    }
+}
+
+impl<C: DefaultCollator> Clone for CollatedSymbol<C> {
+    fn clone(&self) -> Self {
+        CollatedSymbol::new(self.symbol.clone())
+    }
+}
+
+impl<C: DefaultCollator> PartialEq for CollatedSymbol<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.symbol == other.symbol
+    }
+}
+
+impl<C: DefaultCollator> Eq for CollatedSymbol<C> {}
+
+impl<C: DefaultCollator> PartialOrd for CollatedSymbol<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: DefaultCollator> Ord for CollatedSymbol<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // ties are broken by byte ordering, to stay consistent with equality
+        self.symbol.cmp_collated(&other.symbol, C::collator())
+            .then_with(|| self.symbol.cmp(&other.symbol))
+    }
+}
+
+impl<C: DefaultCollator> std::hash::Hash for CollatedSymbol<C> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.symbol.hash(state)
+    }
+}
+
+impl<C: DefaultCollator> std::ops::Deref for CollatedSymbol<C> {
+    type Target = Symbol;
+
+    fn deref(&self) -> &Symbol {
+        &self.symbol
+    }
+}
+
+impl<C: DefaultCollator> std::fmt::Debug for CollatedSymbol<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.symbol, f)
+    }
+}
+
+impl<C: DefaultCollator> std::fmt::Display for CollatedSymbol<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.symbol, f)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    use std::cmp::Ordering;
+
+    struct IgnoreCase;
+
+    impl Collator for IgnoreCase {
+        fn compare(&self, a: &str, b: &str) -> Ordering {
+            a.to_lowercase().cmp(&b.to_lowercase())
+        }
+    }
+
+    impl DefaultCollator for IgnoreCase {
+        fn collator() -> &'static Self {
+            &IgnoreCase
+        }
+    }
+
+    #[test]
+    fn collated_ordering() {
+        let _lock = test_lock();
+
+        let (a, b) = (Symbol::new("apple"), Symbol::new("Banana"));
+        assert_eq!(a.cmp(&b), Ordering::Greater);
+        assert_eq!(a.cmp_collated(&b, &IgnoreCase), Ordering::Less);
+        assert_eq!(a.cmp_collated(&b, &|x: &str, y: &str| x.len().cmp(&y.len())), Ordering::Less);
+
+        let mut names: Vec<CollatedSymbol<IgnoreCase>> = ["b", "C", "a", "A"].iter()
+            .map(|&s| Symbol::new(s).into())
+            .collect();
+        names.sort();
+        let sorted: Vec<&str> = names.iter().map(|s| s.as_str()).collect();
+        assert_eq!(sorted, ["A", "a", "b", "C"]);
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::TryReserveError;
+
+use super::AllocError;
+
+/// Error returned by the non-panicking `try_` variants of `Symbol` and `SymbolMap` operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Memory for a symbol could not be allocated.
+    Alloc(AllocError),
+    /// Requested size exceeds the maximal allocation size.
+    CapacityOverflow,
+    /// Collection capacity could not be reserved.
+    Reserve(TryReserveError),
+    /// Index is out of bounds.
+    IndexOutOfBounds { index: usize, len: usize },
+    /// Symbol table is frozen and new symbols cannot be interned.
+    Frozen,
+    /// String is longer than allowed by [`SymbolLimits`](crate::SymbolLimits).
+    TooLong { len: usize, max: usize },
+    /// String contains a character rejected by [`SymbolLimits`](crate::SymbolLimits).
+    InvalidChar(char),
+    /// String is not a valid identifier, `index` is the byte offset of the first invalid character
+    /// (or 0 for an empty string).
+    InvalidIdent { index: usize },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Alloc(err) => std::fmt::Display::fmt(err, f),
+            Error::CapacityOverflow => f.write_str("capacity overflow"),
+            Error::Reserve(err) => std::fmt::Display::fmt(err, f),
+            Error::IndexOutOfBounds { index, len } => {
+                write!(f, "index {} out of bounds for length {}", index, len)
+            }
+            Error::Frozen => f.write_str("symbol table is frozen"),
+            Error::TooLong { len, max } => {
+                write!(f, "symbol length {} exceeds the limit of {} bytes", len, max)
+            }
+            Error::InvalidChar(c) => write!(f, "symbol contains invalid character {:?}", c),
+            Error::InvalidIdent { index } => write!(f, "invalid identifier at byte {}", index),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Alloc(err) => Some(err),
+            Error::Reserve(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<AllocError> for Error {
+    fn from(err: AllocError) -> Self {
+        Error::Alloc(err)
+    }
+}
+
+impl From<TryReserveError> for Error {
+    fn from(err: TryReserveError) -> Self {
+        Error::Reserve(err)
+    }
+}
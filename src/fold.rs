@@ -0,0 +1,234 @@
+use super::Symbol;
+
+use std::borrow::Cow;
+#[cfg(feature = "casefold")]
+use std::cmp::Ordering;
+#[cfg(feature = "casefold")]
+use std::ptr::NonNull;
+use std::str::Chars;
+#[cfg(feature = "casefold")]
+use std::sync::atomic::{AtomicPtr, Ordering as AtomicOrdering};
+
+/// Iterator over Unicode case-folded characters of a string.
+///
+/// Folding is derived from the standard library case mapping tables, as lowercase of uppercase,
+/// with exceptions for characters where it differs from full case folding (`CaseFolding.txt`,
+/// statuses C and F): dotless `ı` folds to itself and Cherokee letters fold to uppercase.
+struct CaseFold<'a> {
+    chars: Chars<'a>,
+    upper: Option<std::char::ToUppercase>,
+    lower: Option<std::char::ToLowercase>,
+}
+
+impl<'a> CaseFold<'a> {
+    fn new(s: &'a str) -> Self {
+        CaseFold {
+            chars: s.chars(),
+            upper: None,
+            lower: None,
+        }
+    }
+}
+
+fn is_cherokee(c: char) -> bool {
+    matches!(c, '\u{13A0}'..='\u{13F5}' | '\u{13F8}'..='\u{13FD}' | '\u{AB70}'..='\u{ABBF}')
+}
+
+impl<'a> Iterator for CaseFold<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        loop {
+            if let Some(c) = self.lower.as_mut().and_then(|l| l.next()) {
+                return Some(c);
+            }
+            if let Some(c) = self.upper.as_mut().and_then(|u| u.next()) {
+                self.lower = Some(c.to_lowercase());
+                continue;
+            }
+            let c = self.chars.next()?;
+            if c.is_ascii() {
+                return Some(c.to_ascii_lowercase());
+            } else if c == '\u{131}' {
+                return Some(c);
+            } else if is_cherokee(c) {
+                return c.to_uppercase().next();
+            }
+            self.upper = Some(c.to_uppercase());
+        }
+    }
+}
+
+fn fold_str(s: &str) -> String {
+    CaseFold::new(s).collect()
+}
+
+/// Returns case-folded `s`, borrowing it if it is already folded.
+pub(crate) fn fold(s: &str) -> Cow<'_, str> {
+    if s.is_ascii() {
+        if s.bytes().any(|b| b.is_ascii_uppercase()) {
+            Cow::Owned(s.to_ascii_lowercase())
+        } else {
+            Cow::Borrowed(s)
+        }
+    } else if CaseFold::new(s).eq(s.chars()) {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(fold_str(s))
+    }
+}
+
+#[cfg(feature = "casefold")]
+impl Symbol {
+    /// Compares symbols for equality using full Unicode case folding.
+    pub fn eq_fold(&self, other: &Symbol) -> bool {
+        if self == other {
+            return true;
+        }
+        if let (Some(a), Some(b)) = (self.cached_fold(), other.cached_fold()) {
+            return a == b;
+        }
+        let (a, b) = (self.as_str(), other.as_str());
+        if a.is_ascii() && b.is_ascii() {
+            a.eq_ignore_ascii_case(b)
+        } else {
+            CaseFold::new(a).eq(CaseFold::new(b))
+        }
+    }
+
+    /// Compares symbols using full Unicode case folding, ordering by folded code points.
+    pub fn cmp_fold(&self, other: &Symbol) -> Ordering {
+        if self == other {
+            return Ordering::Equal;
+        }
+        CaseFold::new(self.as_str()).cmp(CaseFold::new(other.as_str()))
+    }
+
+    /// Returns case-folded sibling of this symbol. The sibling is cached in the symbol header,
+    /// so subsequent calls and [`Symbol::eq_fold`] comparisons do not fold the string again.
+    pub fn fold_case(&self) -> Symbol {
+        self.companion(&self.header().folded, fold_str)
+    }
+
+    fn cached_fold(&self) -> Option<Symbol> {
+        cached(&self.header().folded)
+    }
+
+    /// Returns sibling cached in `slot`, computing and caching it on first use.
+    fn companion(&self, slot: &AtomicPtr<u8>, f: fn(&str) -> String) -> Symbol {
+        if let Some(s) = cached(slot) {
+            return s;
+        }
+        let value = f(self.as_str());
+        let (s, ptr) = if value == self.as_str() {
+            (self.clone(), self.0)
+        } else {
+            let s = Symbol::new(value);
+            let p = s.0;
+            (s, p)
+        };
+        let cached = slot.compare_exchange(std::ptr::null_mut(), ptr.as_ptr(),
+            AtomicOrdering::AcqRel, AtomicOrdering::Acquire);
+        if cached.is_ok() && ptr != self.0 {
+            // cache holds its own reference to the sibling, released in `take_companions()`
+            std::mem::forget(s.clone());
+        }
+        s
+    }
+}
+
+impl Symbol {
+    /// Returns lowercase sibling of this symbol, as defined by [`str::to_lowercase`].
+    ///
+    /// With `casefold` feature the sibling is cached in the symbol header, so subsequent calls
+    /// do not convert the string again.
+    pub fn to_lowercase_symbol(&self) -> Symbol {
+        #[cfg(feature = "casefold")]
+        {
+            self.companion(&self.header().lower, str::to_lowercase)
+        }
+        #[cfg(not(feature = "casefold"))]
+        {
+            Symbol::new(self.to_lowercase())
+        }
+    }
+}
+
+#[cfg(feature = "casefold")]
+fn cached(slot: &AtomicPtr<u8>) -> Option<Symbol> {
+    let p = NonNull::new(slot.load(AtomicOrdering::Acquire))?;
+    let s = std::mem::ManuallyDrop::new(Symbol(p));
+    Some((*s).clone())
+}
+
+#[cfg(feature = "casefold")]
+fn take(s: &Symbol, slot: &AtomicPtr<u8>) -> Option<Symbol> {
+    let p = NonNull::new(slot.swap(std::ptr::null_mut(), AtomicOrdering::Acquire))?;
+    if p == s.0 {
+        None
+    } else {
+        Some(Symbol(p))
+    }
+}
+
+/// Takes references to the cached folded and lowercase siblings from a symbol being destroyed.
+#[cfg(feature = "casefold")]
+pub(crate) fn take_companions(s: &Symbol) -> (Option<Symbol>, Option<Symbol>) {
+    (take(s, &s.header().folded), take(s, &s.header().lower))
+}
+
+
+#[cfg(all(test, feature = "casefold"))]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    use std::cmp::Ordering;
+
+    #[test]
+    fn full_case_folding() {
+        let _lock = test_lock();
+
+        assert!(Symbol::from("Straße").eq_fold(&Symbol::from("STRASSE")));
+        assert!(Symbol::from("ΌΣΟΣ").eq_fold(&Symbol::from("όσος")));
+        assert!(!Symbol::from("ı").eq_fold(&Symbol::from("I")));
+        assert!(Symbol::from("Hello").eq_fold(&Symbol::from("hELLO")));
+        assert_eq!(Symbol::from("apple").cmp_fold(&Symbol::from("BANANA")), Ordering::Less);
+        assert_eq!(Symbol::from("Straße").cmp_fold(&Symbol::from("strasse")), Ordering::Equal);
+    }
+
+    #[test]
+    fn folded_sibling_is_cached_and_released() {
+        let _lock = test_lock();
+
+        {
+            let s = Symbol::from("MiXeD");
+            let f = s.fold_case();
+            assert_eq!(f, "mixed");
+            assert_eq!(f.ref_count(), 2);
+            assert_eq!(s.fold_case(), f);
+            assert!(f.fold_case() == f);
+            assert!(s.eq_fold(&Symbol::from("mIxEd")));
+        }
+        assert_eq!(Symbol::get("MiXeD"), None);
+        assert_eq!(Symbol::get("mixed"), None);
+    }
+
+    #[test]
+    fn lowercase_sibling_is_cached_and_released() {
+        let _lock = test_lock();
+
+        {
+            let s = Symbol::from("StraßE");
+            let l = s.to_lowercase_symbol();
+            assert_eq!(l, "straße");
+            assert_eq!(l.ref_count(), 2);
+            assert_eq!(s.to_lowercase_symbol(), l);
+            assert!(l.to_lowercase_symbol() == l);
+            assert_eq!(s.fold_case(), "strasse");
+        }
+        assert_eq!(Symbol::get("StraßE"), None);
+        assert_eq!(Symbol::get("straße"), None);
+        assert_eq!(Symbol::get("strasse"), None);
+    }
+}
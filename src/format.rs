@@ -0,0 +1,108 @@
+use super::Symbol;
+use super::buf::StrBuf;
+
+use std::cell::RefCell;
+use std::fmt::Write;
+
+/// Buffers larger than this are not kept for reuse.
+const MAX_RETAINED_CAPACITY: usize = 4096;
+
+thread_local! {
+    static BUFFER: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+impl Symbol {
+    /// Interns formatted string, using a reusable thread-local buffer instead of allocating
+    /// a `String` for every call. Usually called through [`format_symbol!`](crate::format_symbol).
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    pub fn from_args(args: std::fmt::Arguments) -> Symbol {
+        if let Some(s) = args.as_str() {
+            return Symbol::new(s);
+        }
+        intern_buffered(|buf| buf.write_fmt(args))
+    }
+
+    /// Interns concatenation of `parts`, built in a single buffer of the total length.
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    pub fn concat<S: AsRef<str>>(parts: &[S]) -> Symbol {
+        let mut buf = StrBuf::with_capacity(parts.iter().map(|p| p.as_ref().len()).sum());
+        parts.iter().for_each(|p| buf.push_str(p.as_ref()));
+        Symbol::new(buf.as_str())
+    }
+}
+
+/// Interns string written by `write` into the reusable thread-local buffer, or into a new buffer
+/// when the thread-local one is already in use (e.g. a formatted value interns a symbol itself).
+pub(crate) fn intern_buffered<F>(write: F) -> Symbol
+    where F: FnOnce(&mut dyn Write) -> std::fmt::Result
+{
+    BUFFER.with(|buffer| match buffer.try_borrow_mut() {
+        Ok(mut buf) => {
+            buf.clear();
+            write(&mut *buf).expect("a formatting trait implementation returned an error");
+            let s = Symbol::new(buf.as_str());
+            if buf.capacity() > MAX_RETAINED_CAPACITY {
+                *buf = String::new();
+            }
+            s
+        }
+        Err(_) => {
+            let mut buf = StrBuf::new();
+            write(&mut buf).expect("a formatting trait implementation returned an error");
+            Symbol::new(buf.as_str())
+        }
+    })
+}
+
+/// Interns a formatted string, like `Symbol::from(format!(...))` without allocating
+/// an intermediate `String`.
+///
+/// ```
+/// let s = kg_symbol::format_symbol!("user:{}:{}", "acme", 42);
+/// assert_eq!(s, "user:acme:42");
+/// ```
+#[macro_export]
+macro_rules! format_symbol {
+    ($($arg:tt)*) => {
+        $crate::Symbol::from_args(::std::format_args!($($arg)*))
+    };
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    struct Nested;
+
+    impl std::fmt::Display for Nested {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(&format_symbol!("inner:{}", 1))
+        }
+    }
+
+    #[test]
+    fn formats_into_symbol() {
+        let _lock = test_lock();
+
+        let s = format_symbol!("user:{}:{}", "acme", 42);
+        assert_eq!(s, "user:acme:42");
+        assert_eq!(format_symbol!("user:{}:{}", "acme", 42), s);
+        assert_eq!(format_symbol!("plain"), "plain");
+        assert_eq!(format_symbol!("outer:{}", Nested), "outer:inner:1");
+
+        let long = "y".repeat(5000);
+        assert_eq!(Symbol::concat(&[long.as_str(), "!"]), format!("{}!", long).as_str());
+        assert_eq!(format_symbol!("{}", long), long.as_str());
+    }
+
+    #[test]
+    fn concat() {
+        let _lock = test_lock();
+
+        let parent = Symbol::new("parent");
+        assert_eq!(Symbol::concat(&[parent.as_str(), ".", "child"]), "parent.child");
+        assert_eq!(Symbol::concat::<&str>(&[]), Symbol::empty());
+    }
+}
@@ -0,0 +1,59 @@
+use super::{Error, Symbol, SymbolTable, SYMBOLS};
+
+use std::sync::OnceLock;
+
+/// Behavior of interning functions called for unknown strings after [`freeze()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FreezePolicy {
+    /// Fallible functions like [`Symbol::try_new`] return [`Error::Frozen`](crate::Error::Frozen),
+    /// infallible ones panic.
+    Error,
+    /// All interning functions panic.
+    Panic,
+}
+
+pub(crate) struct Frozen {
+    pub(crate) table: SymbolTable,
+    pub(crate) policy: FreezePolicy,
+}
+
+static FROZEN: OnceLock<Frozen> = OnceLock::new();
+
+/// Transitions the global symbol table into a read-only state.
+///
+/// All symbols interned so far become permanent, lookups no longer take any locks,
+/// and interning of new strings fails according to `policy`. Freezing cannot be undone,
+/// subsequent calls have no effect.
+pub fn freeze(policy: FreezePolicy) {
+    let shards = SYMBOLS.write_all();
+    FROZEN.get_or_init(|| {
+        let table: SymbolTable = shards.iter().flat_map(|s| s.iter().filter_map(Symbol::upgrade)).collect();
+        table.iter().for_each(Symbol::pin);
+        Frozen {
+            table,
+            policy,
+        }
+    });
+}
+
+pub fn is_frozen() -> bool {
+    FROZEN.get().is_some()
+}
+
+pub(crate) fn frozen() -> Option<&'static Frozen> {
+    FROZEN.get()
+}
+
+impl Frozen {
+    pub(crate) fn get(&self, value: &str) -> Option<Symbol> {
+        self.table.get(value).cloned()
+    }
+
+    pub(crate) fn try_get(&self, value: &str) -> Result<Symbol, Error> {
+        match self.get(value) {
+            Some(s) => Ok(s),
+            None if self.policy == FreezePolicy::Panic => panic!("symbol table is frozen, cannot intern {:?}", value),
+            None => Err(Error::Frozen),
+        }
+    }
+}
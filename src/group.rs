@@ -0,0 +1,69 @@
+use super::{Symbol, SymbolHashSet};
+
+use std::collections::HashMap;
+
+use parking_lot::Mutex;
+
+lazy_static! {
+    static ref GROUPS: Mutex<HashMap<String, SymbolHashSet>> = Mutex::new(HashMap::new());
+}
+
+impl Symbol {
+    /// Interns `value` and keeps the symbol alive as a member of `group`,
+    /// until the group is purged with [`purge_group`].
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    pub fn new_in_group<S: AsRef<str>>(group: &str, value: S) -> Symbol {
+        let s = Symbol::new(value);
+        let mut groups = GROUPS.lock();
+        match groups.get_mut(group) {
+            Some(members) => {
+                members.insert(s.clone());
+            }
+            None => {
+                let mut members = SymbolHashSet::default();
+                members.insert(s.clone());
+                groups.insert(group.to_string(), members);
+            }
+        }
+        s
+    }
+}
+
+/// Releases references to all symbols interned into `group`, returning the number of released symbols.
+/// Symbols without other references are reclaimed.
+pub fn purge_group(group: &str) -> usize {
+    let members = GROUPS.lock().remove(group);
+    // symbols are dropped outside of the lock
+    members.map_or(0, |m| m.len())
+}
+
+/// Returns number of symbols kept alive by `group`.
+pub fn group_len(group: &str) -> usize {
+    GROUPS.lock().get(group).map_or(0, |m| m.len())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn purge_group_releases_members() {
+        let _lock = test_lock();
+
+        Symbol::new_in_group("request-1", "header");
+        Symbol::new_in_group("request-1", "header");
+        let body = Symbol::new_in_group("request-1", "body");
+        Symbol::new_in_group("request-2", "header");
+        assert_eq!(group_len("request-1"), 2);
+
+        assert_eq!(purge_group("request-1"), 2);
+        assert_eq!(purge_group("request-1"), 0);
+        assert!(Symbol::get("header").is_some());
+        assert_eq!(Symbol::get("body"), Some(body));
+
+        assert_eq!(purge_group("request-2"), 1);
+        assert_eq!(Symbol::get("header"), None);
+    }
+}
@@ -0,0 +1,350 @@
+#[cfg(feature = "global")]
+use super::Symbol;
+
+#[cfg(feature = "global")]
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasherDefault, Hasher};
+#[cfg(feature = "global")]
+use std::hash::Hash;
+#[cfg(feature = "global")]
+use std::ops::Deref;
+
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+/// Fast, non-cryptographic hasher (FxHash algorithm) suited for hashing symbols.
+///
+/// It is not resistant against collision attacks, so it should not be used for keys
+/// coming from untrusted input.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SymbolHasher {
+    hash: u64,
+}
+
+impl SymbolHasher {
+    #[inline(always)]
+    fn add_to_hash(&mut self, i: u64) {
+        self.hash = add_to_hash(self.hash, i);
+    }
+}
+
+#[inline(always)]
+const fn add_to_hash(hash: u64, i: u64) -> u64 {
+    (hash.rotate_left(5) ^ i).wrapping_mul(SEED)
+}
+
+#[inline]
+const fn hash_bytes(mut hash: u64, bytes: &[u8]) -> u64 {
+    let b = bytes;
+    let mut i = 0;
+    while i + 8 <= b.len() {
+        hash = add_to_hash(hash, u64::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3], b[i + 4], b[i + 5], b[i + 6], b[i + 7]]));
+        i += 8;
+    }
+    if i + 4 <= b.len() {
+        hash = add_to_hash(hash, u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]) as u64);
+        i += 4;
+    }
+    if i + 2 <= b.len() {
+        hash = add_to_hash(hash, u16::from_le_bytes([b[i], b[i + 1]]) as u64);
+        i += 2;
+    }
+    if i < b.len() {
+        hash = add_to_hash(hash, b[i] as u64);
+    }
+    hash
+}
+
+/// Returns [`BuildSymbolHasher`] hash of `value`, same as `BuildSymbolHasher::default().hash_one(value)`.
+///
+/// Unlike `hash_one`, it can be evaluated at compile time, which is used to precompute hashes
+/// of static symbols.
+pub const fn hash_str(value: &str) -> u64 {
+    // `Hash for str` appends a terminator byte
+    add_to_hash(hash_bytes(0, value.as_bytes()), 0xff)
+}
+
+impl Hasher for SymbolHasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.hash = hash_bytes(self.hash, bytes);
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.add_to_hash(i);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.add_to_hash(i as u64);
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// Hasher for maps keyed by [`IdentityKey`], which hashes only the symbol address.
+///
+/// Equal symbols share the same string buffer, so hashing an `IdentityKey` takes constant time
+/// regardless of string length. All other values, including `Symbol` itself, `str` and `String`,
+/// are hashed by contents exactly like with [`SymbolHasher`], so the hasher is safe to use
+/// with any key type.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct IdentityHasher {
+    inner: SymbolHasher,
+}
+
+impl Hasher for IdentityHasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.inner.write(bytes);
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.inner.write_u8(i);
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.inner.write_u16(i);
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.inner.write_u32(i);
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.inner.write_u64(i);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        // low bits of aligned addresses are always zero
+        self.inner.write_u64((i as u64).rotate_right(3));
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.inner.finish()
+    }
+}
+
+/// Symbol compared and hashed by address instead of contents.
+///
+/// Since equal symbols share the same address, it is equivalent to the symbol itself as a map key,
+/// but hashing it is a single word hash. It cannot be looked up by `&str`, use
+/// [`IdentityKey::from_ref`] to query maps with a borrowed symbol.
+#[cfg(feature = "global")]
+#[repr(transparent)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct IdentityKey(pub Symbol);
+
+#[cfg(feature = "global")]
+impl IdentityKey {
+    /// Views borrowed symbol as a key, without cloning it.
+    #[inline]
+    pub fn from_ref(s: &Symbol) -> &IdentityKey {
+        // IdentityKey is #[repr(transparent)] over Symbol
+        unsafe { &*(s as *const Symbol as *const IdentityKey) }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Symbol {
+        self.0
+    }
+}
+
+#[cfg(feature = "global")]
+impl Hash for IdentityKey {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.0.0.as_ptr() as usize);
+    }
+}
+
+#[cfg(feature = "global")]
+impl Deref for IdentityKey {
+    type Target = Symbol;
+
+    fn deref(&self) -> &Symbol {
+        &self.0
+    }
+}
+
+#[cfg(feature = "global")]
+impl From<Symbol> for IdentityKey {
+    fn from(s: Symbol) -> Self {
+        IdentityKey(s)
+    }
+}
+
+#[cfg(feature = "global")]
+impl From<IdentityKey> for Symbol {
+    fn from(k: IdentityKey) -> Self {
+        k.0
+    }
+}
+
+#[cfg(feature = "global")]
+impl heapsize::HeapSizeOf for IdentityKey {
+    fn heap_size_of_children(&self) -> usize {
+        self.0.heap_size_of_children()
+    }
+}
+
+#[cfg(feature = "global")]
+impl std::fmt::Debug for IdentityKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+pub type BuildSymbolHasher = BuildHasherDefault<SymbolHasher>;
+
+pub type BuildIdentityHasher = BuildHasherDefault<IdentityHasher>;
+
+#[cfg(feature = "global")]
+pub type SymbolHashMap<V> = HashMap<Symbol, V, BuildSymbolHasher>;
+
+#[cfg(feature = "global")]
+pub type SymbolHashSet = HashSet<Symbol, BuildSymbolHasher>;
+
+/// Hash map keyed by symbols hashed by address, see [`IdentityKey`].
+#[cfg(feature = "global")]
+pub type IdentityHashMap<V> = HashMap<IdentityKey, V, BuildIdentityHasher>;
+
+/// Hash set of symbols hashed by address, see [`IdentityKey`].
+#[cfg(feature = "global")]
+pub type IdentityHashSet = HashSet<IdentityKey, BuildIdentityHasher>;
+
+/// Hasher builder used by hash tables internal to this crate, including the global symbol table.
+///
+/// With `deterministic` feature enabled it is [`BuildSymbolHasher`] which uses a fixed seed,
+/// so that iteration order of these tables is reproducible between runs.
+/// Otherwise it is randomly seeded [`RandomState`](std::collections::hash_map::RandomState).
+#[cfg(feature = "deterministic")]
+pub type DefaultHashBuilder = BuildSymbolHasher;
+
+#[cfg(not(feature = "deterministic"))]
+pub type DefaultHashBuilder = std::collections::hash_map::RandomState;
+
+
+#[cfg(all(test, feature = "global"))]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    use std::collections::HashMap;
+    use std::hash::{BuildHasher, Hash};
+
+    fn hash<T: Hash + ?Sized>(value: &T) -> u64 {
+        BuildSymbolHasher::default().hash_one(value)
+    }
+
+    #[test]
+    fn symbol_hash_eq_str_hash() {
+        let _lock = test_lock();
+
+        for s in ["", "a", "abc", "abcdefg", "abcdefghijklmnopqrstuvwxyz"].iter() {
+            assert_eq!(hash(&Symbol::new(s)), hash(*s));
+        }
+        assert_ne!(hash("abc"), hash("abd"));
+    }
+
+    #[test]
+    fn identity_hash_map() {
+        let _lock = test_lock();
+
+        let mut map = IdentityHashMap::default();
+        map.insert(Symbol::from("one").into(), 1);
+        map.insert(Symbol::from("two").into(), 2);
+        map.insert(Symbol::from("one").into(), 3);
+
+        let mut set = IdentityHashSet::default();
+        set.insert(Symbol::from("one").into());
+
+        let one = Symbol::from("one");
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(IdentityKey::from_ref(&one)), Some(&3));
+        assert_eq!(map.get(&IdentityKey(Symbol::from("three"))), None);
+        assert!(set.contains(IdentityKey::from_ref(&one)));
+        assert!(!set.contains(&IdentityKey(Symbol::from("two"))));
+
+        let key = IdentityKey(one.clone());
+        assert_eq!(BuildIdentityHasher::default().hash_one(&key), BuildIdentityHasher::default().hash_one(IdentityKey::from_ref(&one)));
+    }
+
+    #[test]
+    fn identity_hasher_hashes_other_keys_by_contents() {
+        let _lock = test_lock();
+
+        let mut map: HashMap<String, i32, BuildIdentityHasher> = HashMap::default();
+        map.insert("one".to_string(), 1);
+        map.insert(String::from("t") + "wo", 2);
+
+        let mut symbols: HashMap<Symbol, i32, BuildIdentityHasher> = HashMap::default();
+        symbols.insert(Symbol::from("one"), 1);
+
+        assert_eq!(map.get("one"), Some(&1));
+        assert_eq!(map.get("two"), Some(&2));
+        assert_eq!(symbols.get("one"), Some(&1));
+        assert_eq!(BuildIdentityHasher::default().hash_one("abc"), hash("abc"));
+        assert_eq!(BuildIdentityHasher::default().hash_one(("abc", 1u8)), hash(&("abc", 1u8)));
+    }
+
+    #[test]
+    fn const_hash_eq_str_hash() {
+        const HASH: u64 = hash_str("tab\t\u{1F600}");
+
+        assert_eq!(HASH, hash("tab\t\u{1F600}"));
+        for s in ["", "a", "abcdefg", "zażółć gęślą jaźń", "\x41\\\"\0", "abcdefghijklmnopqrstuvwxyz"].iter() {
+            assert_eq!(hash_str(s), hash(*s));
+        }
+    }
+
+    #[test]
+    fn precomputed_hash_eq_str_hash() {
+        let _lock = test_lock();
+
+        for s in ["", "a", "abcdefghijklmnopqrstuvwxyz"].iter() {
+            assert_eq!(Symbol::new(s).precomputed_hash(), hash(*s));
+        }
+    }
+
+    #[test]
+    fn hash_map_lookup_by_str() {
+        let _lock = test_lock();
+
+        let mut map = SymbolHashMap::default();
+        map.insert(Symbol::from("one"), 1);
+        map.insert(Symbol::from("two"), 2);
+
+        let mut set = SymbolHashSet::default();
+        set.insert(Symbol::from("one"));
+
+        assert_eq!(map.get("one"), Some(&1));
+        assert_eq!(map.get("three"), None);
+        assert!(set.contains("one"));
+        assert!(!set.contains("two"));
+    }
+}
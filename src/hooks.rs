@@ -0,0 +1,137 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use parking_lot::RwLock;
+
+struct Hooks {
+    on_intern: Vec<fn(&str)>,
+    on_free: Vec<fn(&str)>,
+}
+
+static HOOKS: RwLock<Hooks> = parking_lot::const_rwlock(Hooks { on_intern: Vec::new(), on_free: Vec::new() });
+
+/// Set when any hook is registered, so that symbols are created and destroyed
+/// without touching `HOOKS` otherwise.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Set while the current thread runs hooks, with the global symbol table locked.
+    static RUNNING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Registers `hook` called with the string of every newly created symbol.
+///
+/// Hooks are called with the global symbol table locked, so they must not create, look up
+/// or drop symbols, and panic if they do.
+pub fn on_intern(hook: fn(&str)) {
+    HOOKS.write().on_intern.push(hook);
+    ENABLED.store(true, Ordering::Release);
+}
+
+/// Registers `hook` called with the string of every symbol being destroyed.
+///
+/// Hooks are called with the global symbol table locked, so they must not create, look up
+/// or drop symbols, and panic if they do.
+pub fn on_free(hook: fn(&str)) {
+    HOOKS.write().on_free.push(hook);
+    ENABLED.store(true, Ordering::Release);
+}
+
+/// Removes all registered hooks.
+pub fn clear_hooks() {
+    let mut hooks = HOOKS.write();
+    hooks.on_intern.clear();
+    hooks.on_free.clear();
+    ENABLED.store(false, Ordering::Release);
+}
+
+#[inline]
+pub(crate) fn interned(value: &str) {
+    if ENABLED.load(Ordering::Acquire) {
+        run(&HOOKS.read().on_intern, value);
+    }
+}
+
+#[inline]
+pub(crate) fn freed(value: &str) {
+    if ENABLED.load(Ordering::Acquire) {
+        run(&HOOKS.read().on_free, value);
+    }
+}
+
+fn run(hooks: &[fn(&str)], value: &str) {
+    struct Reset;
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            RUNNING.with(|r| r.set(false));
+        }
+    }
+
+    RUNNING.with(|r| r.set(true));
+    let _reset = Reset;
+    hooks.iter().for_each(|h| h(value));
+}
+
+/// Panics if called from a hook, since locking the global symbol table again would deadlock.
+#[inline]
+pub(crate) fn check_reentrancy() {
+    if ENABLED.load(Ordering::Relaxed) && RUNNING.with(Cell::get) {
+        panic!("symbol hooks must not create, look up or drop symbols, the symbol table is locked while they run");
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    use parking_lot::Mutex;
+
+    static EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    fn record_intern(value: &str) {
+        EVENTS.lock().push(format!("+{}", value));
+    }
+
+    fn record_free(value: &str) {
+        EVENTS.lock().push(format!("-{}", value));
+    }
+
+    #[test]
+    fn hooks_observe_lifecycle() {
+        let _lock = test_lock();
+
+        on_intern(record_intern);
+        on_free(record_free);
+        let s = Symbol::new("hooked");
+        let s2 = Symbol::new("hooked");
+        drop((s, s2));
+        clear_hooks();
+        drop(Symbol::new("not hooked"));
+
+        assert_eq!(*EVENTS.lock(), ["+hooked", "-hooked"]);
+    }
+
+    fn intern_from_hook(value: &str) {
+        if value == "reentrant" {
+            Symbol::new("from hook");
+        }
+    }
+
+    #[test]
+    fn hooks_using_symbols_panic() {
+        let _lock = test_lock();
+
+        on_free(intern_from_hook);
+        let s = Symbol::new("reentrant");
+        let res = std::panic::catch_unwind(move || drop(s));
+        clear_hooks();
+
+        assert!(res.is_err());
+        // table is not left locked
+        assert_eq!(Symbol::get("reentrant"), None);
+        assert_eq!(Symbol::get("from hook"), None);
+    }
+}
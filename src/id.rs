@@ -0,0 +1,224 @@
+use super::{Error, Symbol, SYMBOLS};
+
+use std::ptr::{null_mut, NonNull};
+use std::sync::atomic::{AtomicPtr, AtomicU32, AtomicU64, Ordering};
+
+/// Number of slots in the first bucket, as a power of two. Each next bucket is twice as large.
+const FIRST_BUCKET_BITS: u32 = 6;
+
+/// Number of buckets needed to hold ids up to `u32::MAX - 1`.
+const BUCKET_COUNT: usize = (33 - FIRST_BUCKET_BITS) as usize;
+
+/// Slot of a symbol id. Like table entries, slots do not own a reference,
+/// and are cleared when the symbol is destroyed.
+///
+/// Pointer is set and cleared only while holding the write lock of the table shard
+/// selected by `hash`, so it can be safely dereferenced under the read lock of that shard.
+#[derive(Default)]
+struct Slot {
+    ptr: AtomicPtr<u8>,
+    hash: AtomicU64,
+    /// Next released id plus one, or zero, while the id is on the free list.
+    next_free: AtomicU32,
+}
+
+/// Lazily allocated buckets of slots, which are never moved, so that slots can be
+/// accessed without locking.
+static BUCKETS: [AtomicPtr<Slot>; BUCKET_COUNT] = [const { AtomicPtr::new(null_mut()) }; BUCKET_COUNT];
+
+/// Next never used id.
+static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+
+/// Head of the list of released ids: released id plus one (or zero) in low bits,
+/// and a counter of updates in high bits, so that concurrent pops cannot be confused
+/// by the same id being popped and pushed back in the meantime.
+static FREE_IDS: AtomicU64 = AtomicU64::new(0);
+
+#[inline]
+fn bucket_len(bucket: usize) -> usize {
+    1 << (bucket as u32 + FIRST_BUCKET_BITS)
+}
+
+#[inline]
+fn locate(id: u32) -> (usize, usize) {
+    let index = id as u64 + (1 << FIRST_BUCKET_BITS);
+    let bucket = (63 - index.leading_zeros() - FIRST_BUCKET_BITS) as usize;
+    (bucket, (index - bucket_len(bucket) as u64) as usize)
+}
+
+/// Returns slot of `id`, if its bucket is allocated.
+#[inline]
+fn get_slot(id: u32) -> Option<&'static Slot> {
+    let (bucket, offset) = locate(id);
+    let slots = BUCKETS[bucket].load(Ordering::Acquire);
+    if slots.is_null() {
+        None
+    } else {
+        // buckets are never deallocated
+        Some(unsafe { &*slots.add(offset) })
+    }
+}
+
+/// Returns slot of `id`, allocating its bucket if needed.
+fn slot(id: u32) -> &'static Slot {
+    if let Some(slot) = get_slot(id) {
+        return slot;
+    }
+    let (bucket, _) = locate(id);
+    let slots: Box<[Slot]> = (0..bucket_len(bucket)).map(|_| Slot::default()).collect();
+    let slots = Box::into_raw(slots) as *mut Slot;
+    if BUCKETS[bucket].compare_exchange(null_mut(), slots, Ordering::AcqRel, Ordering::Acquire).is_err() {
+        // bucket allocated concurrently by another thread
+        unsafe { free_bucket(bucket, slots) };
+    }
+    get_slot(id).unwrap()
+}
+
+unsafe fn free_bucket(bucket: usize, slots: *mut Slot) {
+    drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(slots, bucket_len(bucket))));
+}
+
+#[inline]
+fn next_tag(head: u64) -> u64 {
+    (((head >> 32) as u32).wrapping_add(1) as u64) << 32
+}
+
+/// Reserves id for a new symbol, reusing ids of destroyed symbols first, so that ids
+/// stay dense under churn.
+pub(crate) fn reserve_id() -> Result<u32, Error> {
+    let mut head = FREE_IDS.load(Ordering::Acquire);
+    while head as u32 != 0 {
+        let id = head as u32 - 1;
+        let next = get_slot(id).unwrap().next_free.load(Ordering::Relaxed);
+        match FREE_IDS.compare_exchange_weak(head, next_tag(head) | next as u64, Ordering::Acquire, Ordering::Acquire) {
+            Ok(_) => return Ok(id),
+            Err(h) => head = h,
+        }
+    }
+    // `u32::MAX` is not used, so that every id plus one fits in `u32`
+    let id = NEXT_ID.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_add(1).filter(|&n| n < u32::MAX))
+        .map_err(|_| Error::CapacityOverflow)?;
+    slot(id);
+    Ok(id)
+}
+
+/// Makes fully initialized symbol resolvable by its reserved id.
+/// Must be called with the write lock of the table shard selected by `hash`.
+pub(crate) fn bind_id(id: u32, p: NonNull<u8>, hash: u64) {
+    let slot = slot(id);
+    slot.hash.store(hash, Ordering::Relaxed);
+    slot.ptr.store(p.as_ptr(), Ordering::Release);
+}
+
+/// Clears slot of a symbol being destroyed and makes its id available for reuse.
+/// Must be called with the write lock of the symbol's table shard, before the symbol is deallocated.
+pub(crate) fn release_id(id: u32) {
+    let slot = slot(id);
+    slot.ptr.store(null_mut(), Ordering::Release);
+    let mut head = FREE_IDS.load(Ordering::Relaxed);
+    loop {
+        slot.next_free.store(head as u32, Ordering::Relaxed);
+        match FREE_IDS.compare_exchange_weak(head, next_tag(head) | (id as u64 + 1), Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => return,
+            Err(h) => head = h,
+        }
+    }
+}
+
+/// Returns pointer to the symbol with given `id`, which is only valid as long as the symbol is alive.
+#[cfg(feature = "atom")]
+pub(crate) fn lookup_id(id: u32) -> Option<NonNull<u8>> {
+    NonNull::new(get_slot(id)?.ptr.load(Ordering::Acquire))
+}
+
+impl Symbol {
+    /// Returns dense numeric id of this symbol, assigned when the symbol was created.
+    ///
+    /// Ids are unique among live symbols. Ids of destroyed symbols are reused for new ones,
+    /// so an id of a symbol which is no longer referenced may later resolve to a different string.
+    #[inline]
+    pub fn id(&self) -> u32 {
+        self.header().id
+    }
+
+    /// Returns symbol with given `id`, if it is still alive.
+    pub fn from_id(id: u32) -> Option<Symbol> {
+        if id == u32::MAX {
+            return None;
+        }
+        let slot = get_slot(id)?;
+        loop {
+            let hash = slot.hash.load(Ordering::Acquire);
+            let shard = SYMBOLS.shard_hashed(hash);
+            let _guard = shard.read();
+            let p = NonNull::new(slot.ptr.load(Ordering::Acquire))?;
+            // slot might have been reused by a symbol from a different shard in the meantime,
+            // otherwise it cannot be cleared, nor the symbol deallocated, while the lock is held
+            if std::ptr::eq(SYMBOLS.shard_hashed(slot.hash.load(Ordering::Relaxed)), shard) {
+                return unsafe { Symbol::upgrade_raw(p) };
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn ids_resolve_live_symbols() {
+        let _lock = test_lock();
+
+        let a = Symbol::new("id a");
+        let b = Symbol::new("id b");
+        assert_ne!(a.id(), b.id());
+        assert_eq!(Symbol::new("id a").id(), a.id());
+        assert_eq!(Symbol::from_id(a.id()), Some(a.clone()));
+        assert_eq!(Symbol::from_id(Symbol::empty().id()), Some(Symbol::empty()));
+        assert_eq!(Symbol::from_id(u32::MAX), None);
+
+        let id = a.id();
+        drop(a);
+        assert_eq!(Symbol::from_id(id), None);
+    }
+
+    #[test]
+    fn ids_of_destroyed_symbols_are_reused() {
+        let _lock = test_lock();
+
+        let ids: Vec<u32> = (0..100).map(|i| Symbol::new(format!("churn {}", i)).id()).collect();
+        let first = ids[0];
+        assert!(ids.iter().all(|&id| id == first));
+
+        let a = Symbol::new("id a");
+        let id = a.id();
+        drop(a);
+        let c = Symbol::new("id c");
+        assert_eq!(c.id(), id);
+        assert_eq!(Symbol::from_id(id), Some(c));
+    }
+
+    #[test]
+    fn concurrent_churn_and_resolution() {
+        let _lock = test_lock();
+
+        let threads: Vec<_> = (0..8).map(|i| {
+            std::thread::spawn(move || {
+                for n in 0..2_000u32 {
+                    let s = Symbol::new(format!("churn {} {}", i, n % 16));
+                    assert_eq!(Symbol::from_id(s.id()).as_ref(), Some(&s));
+                    // resolved symbol may belong to another thread, but must be alive
+                    if let Some(other) = Symbol::from_id(n % 64) {
+                        assert_eq!(other.id(), n % 64);
+                        assert_eq!(other.char_len(), other.as_str().chars().count());
+                    }
+                }
+            })
+        }).collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+    }
+}
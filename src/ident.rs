@@ -0,0 +1,164 @@
+use super::{Error, Symbol};
+
+use std::ops::Deref;
+
+/// Identifier grammar, given by predicates for the first and the following characters.
+#[derive(Clone, Copy)]
+pub struct IdentGrammar {
+    pub start: fn(char) -> bool,
+    pub rest: fn(char) -> bool,
+}
+
+impl IdentGrammar {
+    /// ASCII identifiers, `[A-Za-z_][A-Za-z0-9_]*`.
+    pub const ASCII: IdentGrammar = IdentGrammar {
+        start: |c| c.is_ascii_alphabetic() || c == '_',
+        rest: |c| c.is_ascii_alphanumeric() || c == '_',
+    };
+
+    /// Unicode identifiers, starting with an alphabetic character or `_`, followed by
+    /// alphanumeric characters or `_`.
+    pub const UNICODE: IdentGrammar = IdentGrammar {
+        start: |c| c.is_alphabetic() || c == '_',
+        rest: |c| c.is_alphanumeric() || c == '_',
+    };
+
+    /// Checks `value` against the grammar, returning [`Error::InvalidIdent`] on failure.
+    pub fn check(&self, value: &str) -> Result<(), Error> {
+        let mut chars = value.char_indices();
+        match chars.next() {
+            Some((_, c)) if (self.start)(c) => {}
+            _ => return Err(Error::InvalidIdent { index: 0 }),
+        }
+        match chars.find(|&(_, c)| !(self.rest)(c)) {
+            Some((index, _)) => Err(Error::InvalidIdent { index }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for IdentGrammar {
+    fn default() -> Self {
+        IdentGrammar::ASCII
+    }
+}
+
+impl std::fmt::Debug for IdentGrammar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("IdentGrammar")
+    }
+}
+
+/// Symbol guaranteed to be a valid identifier.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IdentSymbol(Symbol);
+
+impl IdentSymbol {
+    /// Creates identifier symbol, validated with the default ASCII grammar.
+    pub fn new<S: AsRef<str>>(value: S) -> Result<IdentSymbol, Error> {
+        IdentSymbol::with_grammar(value, &IdentGrammar::default())
+    }
+
+    pub fn with_grammar<S: AsRef<str>>(value: S, grammar: &IdentGrammar) -> Result<IdentSymbol, Error> {
+        let value = value.as_ref();
+        grammar.check(value)?;
+        Ok(IdentSymbol(Symbol::try_new(value)?))
+    }
+
+    /// Validates an already interned symbol.
+    pub fn from_symbol(s: Symbol, grammar: &IdentGrammar) -> Result<IdentSymbol, Error> {
+        grammar.check(&s)?;
+        Ok(IdentSymbol(s))
+    }
+
+    pub fn as_symbol(&self) -> &Symbol {
+        &self.0
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl Deref for IdentSymbol {
+    type Target = Symbol;
+
+    fn deref(&self) -> &Symbol {
+        &self.0
+    }
+}
+
+impl AsRef<str> for IdentSymbol {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<IdentSymbol> for Symbol {
+    fn from(s: IdentSymbol) -> Self {
+        s.0
+    }
+}
+
+impl std::convert::TryFrom<Symbol> for IdentSymbol {
+    type Error = Error;
+
+    fn try_from(s: Symbol) -> Result<Self, Self::Error> {
+        IdentSymbol::from_symbol(s, &IdentGrammar::default())
+    }
+}
+
+impl<'a> std::convert::TryFrom<&'a str> for IdentSymbol {
+    type Error = Error;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        IdentSymbol::new(value)
+    }
+}
+
+impl PartialEq<str> for IdentSymbol {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for IdentSymbol {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl std::fmt::Debug for IdentSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl std::fmt::Display for IdentSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.0, f)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    use std::convert::TryFrom;
+
+    #[test]
+    fn identifiers_are_validated() {
+        let _lock = test_lock();
+
+        assert_eq!(IdentSymbol::new("_foo1").unwrap(), "_foo1");
+        assert_eq!(IdentSymbol::new("1foo"), Err(Error::InvalidIdent { index: 0 }));
+        assert_eq!(IdentSymbol::new(""), Err(Error::InvalidIdent { index: 0 }));
+        assert_eq!(IdentSymbol::new("foo-bar"), Err(Error::InvalidIdent { index: 3 }));
+        assert_eq!(IdentSymbol::new("żółw"), Err(Error::InvalidIdent { index: 0 }));
+        assert_eq!(IdentSymbol::with_grammar("żółw", &IdentGrammar::UNICODE).unwrap(), "żółw");
+        assert_eq!(IdentSymbol::try_from(Symbol::new("a b")), Err(Error::InvalidIdent { index: 1 }));
+        assert_eq!(Symbol::from(IdentSymbol::try_from("ok").unwrap()), Symbol::new("ok"));
+    }
+}
@@ -0,0 +1,269 @@
+use super::{DefaultHashBuilder, Normalizer};
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+
+use parking_lot::RwLock;
+
+/// Symbol pool independent of the global symbol table.
+///
+/// Strings interned in an `Interner` are represented by [`LocalSymbol`] handles, which are
+/// compared by pointer, like [`Symbol`](crate::Symbol). Strings stay in the pool until
+/// [`Interner::purge`] is called or the interner is dropped.
+///
+/// With the default `global` feature disabled, the global symbol table and all types built
+/// on [`Symbol`](crate::Symbol) are compiled out, and interners are the only way to intern strings.
+#[derive(Default)]
+pub struct Interner {
+    table: RwLock<HashSet<Arc<str>, DefaultHashBuilder>>,
+    normalizer: Option<Box<dyn Normalizer>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    purged: AtomicU64,
+}
+
+/// Usage statistics of an [`Interner`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InternerStats {
+    /// Number of strings currently in the interner.
+    pub len: usize,
+    /// Number of lookups which found an already interned string.
+    pub hits: u64,
+    /// Number of lookups which did not find the string.
+    pub misses: u64,
+    /// Number of strings removed by [`Interner::purge`].
+    pub purged: u64,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    /// Creates interner canonicalizing all strings with `normalizer` before lookup or insertion.
+    pub fn with_normalizer<N: Normalizer + 'static>(normalizer: N) -> Self {
+        Interner {
+            normalizer: Some(Box::new(normalizer)),
+            ..Interner::default()
+        }
+    }
+
+    pub fn intern(&self, value: &str) -> LocalSymbol {
+        let value = match &self.normalizer {
+            Some(n) => n.normalize(value),
+            None => value.into(),
+        };
+        if let Some(s) = self.table.read().get(value.as_ref()) {
+            self.hits.fetch_add(1, AtomicOrdering::Relaxed);
+            return LocalSymbol(s.clone());
+        }
+
+        let mut table = self.table.write();
+        if let Some(s) = table.get(value.as_ref()) {
+            self.hits.fetch_add(1, AtomicOrdering::Relaxed);
+            return LocalSymbol(s.clone());
+        }
+        self.misses.fetch_add(1, AtomicOrdering::Relaxed);
+        let s: Arc<str> = value.as_ref().into();
+        table.insert(s.clone());
+        LocalSymbol(s)
+    }
+
+    pub fn get(&self, value: &str) -> Option<LocalSymbol> {
+        let value = match &self.normalizer {
+            Some(n) => n.normalize(value),
+            None => value.into(),
+        };
+        let s = self.table.read().get(value.as_ref()).map(|s| LocalSymbol(s.clone()));
+        let counter = if s.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, AtomicOrdering::Relaxed);
+        s
+    }
+
+    /// Returns `true` if `s` was interned in this interner.
+    pub fn contains(&self, s: &LocalSymbol) -> bool {
+        self.table.read().get(s.as_str()).is_some_and(|e| Arc::ptr_eq(e, &s.0))
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.read().is_empty()
+    }
+
+    /// Removes strings without live [`LocalSymbol`] handles, returning the number of removed strings.
+    pub fn purge(&self) -> usize {
+        let mut table = self.table.write();
+        let len = table.len();
+        table.retain(|s| Arc::strong_count(s) > 1);
+        let purged = len - table.len();
+        self.purged.fetch_add(purged as u64, AtomicOrdering::Relaxed);
+        purged
+    }
+
+    pub fn stats(&self) -> InternerStats {
+        InternerStats {
+            len: self.len(),
+            hits: self.hits.load(AtomicOrdering::Relaxed),
+            misses: self.misses.load(AtomicOrdering::Relaxed),
+            purged: self.purged.load(AtomicOrdering::Relaxed),
+        }
+    }
+}
+
+impl std::fmt::Debug for Interner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Interner")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+
+/// Handle to a string interned in an [`Interner`].
+#[derive(Clone)]
+pub struct LocalSymbol(Arc<str>);
+
+impl LocalSymbol {
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for LocalSymbol {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for LocalSymbol {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq for LocalSymbol {
+    #[inline]
+    fn eq(&self, other: &LocalSymbol) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for LocalSymbol {}
+
+impl PartialEq<str> for LocalSymbol {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for LocalSymbol {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialOrd for LocalSymbol {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LocalSymbol {
+    /// Orders by string, equal strings from different interners are ordered by address,
+    /// consistently with `Eq`.
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.eq(other) {
+            Ordering::Equal
+        } else {
+            self.as_str().cmp(other.as_str())
+                .then_with(|| Arc::as_ptr(&self.0).cast::<u8>().cmp(&Arc::as_ptr(&other.0).cast::<u8>()))
+        }
+    }
+}
+
+impl Hash for LocalSymbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl std::fmt::Debug for LocalSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl std::fmt::Display for LocalSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn interners_are_isolated() {
+        let i1 = Interner::new();
+        let i2 = Interner::new();
+
+        let a1 = i1.intern("a");
+        let a2 = i2.intern("a");
+        assert_eq!(i1.intern("a"), a1);
+        assert_ne!(a1, a2);
+        assert_eq!(a1, "a");
+        assert!(i1.contains(&a1));
+        assert!(!i1.contains(&a2));
+        assert_eq!(i1.get("b"), None);
+    }
+
+    #[test]
+    fn purge_removes_unreferenced_strings() {
+        let i = Interner::new();
+        let a = i.intern("a");
+        i.intern("b");
+
+        assert_eq!(i.len(), 2);
+        assert_eq!(i.purge(), 1);
+        assert_eq!(i.get("a"), Some(a));
+        assert_eq!(i.get("b"), None);
+        assert_eq!(i.stats(), InternerStats { len: 1, hits: 1, misses: 3, purged: 1 });
+    }
+
+    #[test]
+    fn normalizer_is_applied() {
+        let i = Interner::with_normalizer(Lowercase);
+        assert_eq!(i.intern("ABC"), i.intern("abc"));
+        assert_eq!(i.get("Abc").as_ref().map(|s| s.as_str()), Some("abc"));
+    }
+
+    #[test]
+    fn ordering_is_consistent_with_equality() {
+        let i1 = Interner::new();
+        let i2 = Interner::new();
+
+        let a1 = i1.intern("a");
+        let a2 = i2.intern("a");
+        assert_ne!(a1.cmp(&a2), std::cmp::Ordering::Equal);
+        assert_eq!(a1.cmp(&a2), a2.cmp(&a1).reverse());
+        assert_eq!(a1.cmp(&i1.intern("a")), std::cmp::Ordering::Equal);
+        assert!(a1 < i1.intern("b") && a2 < i1.intern("b"));
+
+        let set: std::collections::BTreeSet<_> = vec![a1, a2].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+}
@@ -0,0 +1,187 @@
+use super::{sort_symbols, Symbol, SymbolMap, SYMBOLS};
+
+use std::hash::Hash;
+use std::io::{Read, Write};
+
+use serde::de::Error;
+use serde_json::{json, Map, Value};
+
+/// Version of the JSON structure written by [`export_json`].
+pub const EXPORT_JSON_VERSION: u64 = 1;
+
+impl SymbolMap<Value> {
+    pub fn get_str<Q>(&self, k: &Q) -> Option<&str>
+        where Q: ?Sized + AsRef<str> + Hash + Eq
+    {
+        self.get(k).and_then(Value::as_str)
+    }
+
+    pub fn get_i64<Q>(&self, k: &Q) -> Option<i64>
+        where Q: ?Sized + AsRef<str> + Hash + Eq
+    {
+        self.get(k).and_then(Value::as_i64)
+    }
+
+    pub fn get_u64<Q>(&self, k: &Q) -> Option<u64>
+        where Q: ?Sized + AsRef<str> + Hash + Eq
+    {
+        self.get(k).and_then(Value::as_u64)
+    }
+
+    pub fn get_f64<Q>(&self, k: &Q) -> Option<f64>
+        where Q: ?Sized + AsRef<str> + Hash + Eq
+    {
+        self.get(k).and_then(Value::as_f64)
+    }
+
+    pub fn get_bool<Q>(&self, k: &Q) -> Option<bool>
+        where Q: ?Sized + AsRef<str> + Hash + Eq
+    {
+        self.get(k).and_then(Value::as_bool)
+    }
+
+    pub fn get_array<Q>(&self, k: &Q) -> Option<&Vec<Value>>
+        where Q: ?Sized + AsRef<str> + Hash + Eq
+    {
+        self.get(k).and_then(Value::as_array)
+    }
+
+    pub fn get_map<Q>(&self, k: &Q) -> Option<&Map<String, Value>>
+        where Q: ?Sized + AsRef<str> + Hash + Eq
+    {
+        self.get(k).and_then(Value::as_object)
+    }
+}
+
+/// Keys which become equal after normalization keep the value coming last in `map`.
+impl From<Map<String, Value>> for SymbolMap<Value> {
+    fn from(map: Map<String, Value>) -> Self {
+        SymbolMap::from_string_pairs(map)
+    }
+}
+
+impl From<SymbolMap<Value>> for Map<String, Value> {
+    fn from(map: SymbolMap<Value>) -> Self {
+        map.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+    }
+}
+
+/// Writes all live symbols as JSON.
+///
+/// Written structure is:
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "symbols": [
+///     { "id": 0, "value": "", "ref_count": 1 },
+///     { "id": 1, "value": "example", "ref_count": 3 }
+///   ]
+/// }
+/// ```
+///
+/// Symbols are sorted by value, `id` is the [`Symbol::id`] of each symbol and `ref_count` is the number
+/// of live handles at the moment of export.
+pub fn export_json<W: Write>(w: W) -> serde_json::Result<()> {
+    let symbols: Vec<(Symbol, usize)> = {
+        let mut symbols = SYMBOLS.symbols();
+        sort_symbols(&mut symbols);
+        // subtract reference held by the vector
+        symbols.into_iter().map(|s| { let rc = s.ref_count() - 1; (s, rc) }).collect()
+    };
+
+    let entries: Vec<Value> = symbols.iter()
+        .map(|(s, rc)| json!({ "id": s.id(), "value": s.as_str(), "ref_count": rc }))
+        .collect();
+    serde_json::to_writer_pretty(w, &json!({
+        "version": EXPORT_JSON_VERSION,
+        "symbols": entries,
+    }))
+}
+
+/// Reads symbols from JSON written by [`export_json`] and interns them.
+///
+/// Only `value` of each entry is required, other fields (like `id`, `ref_count` or `tags`)
+/// are ignored. Entries can also be plain strings. Returned symbols keep imported strings alive.
+pub fn import_json<R: Read>(r: R) -> serde_json::Result<Vec<Symbol>> {
+    let value: Value = serde_json::from_reader(r)?;
+    let entries = value.get("symbols")
+        .and_then(Value::as_array)
+        .ok_or_else(|| serde_json::Error::custom("missing \"symbols\" array"))?;
+    entries.iter()
+        .map(|e| {
+            e.as_str()
+                .or_else(|| e.get("value").and_then(Value::as_str))
+                .map(Symbol::new)
+                .ok_or_else(|| serde_json::Error::custom("symbol entry without \"value\" string"))
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    use serde_json::{json, Map, Value};
+
+    #[test]
+    fn typed_accessors() {
+        let _lock = test_lock();
+
+        let value = json!({
+            "name": "example",
+            "count": -3,
+            "enabled": true,
+            "nested": { "a": 1 },
+            "items": [1, 2],
+        });
+        let map = match value {
+            Value::Object(map) => SymbolMap::from(map),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(map.get_str("name"), Some("example"));
+        assert_eq!(map.get_i64("count"), Some(-3));
+        assert_eq!(map.get_u64("count"), None);
+        assert_eq!(map.get_bool("enabled"), Some(true));
+        assert_eq!(map.get_map("nested").and_then(|m| m.get("a")), Some(&json!(1)));
+        assert_eq!(map.get_array("items").map(|a| a.len()), Some(2));
+        assert_eq!(map.get_str("count"), None);
+        assert_eq!(map.get_str("missing"), None);
+
+        let back = Map::from(map);
+        assert_eq!(back.get("name"), Some(&json!("example")));
+        assert_eq!(back.len(), 5);
+    }
+
+    #[test]
+    fn export_import_round_trip() {
+        let _lock = test_lock();
+
+        let (json, ids) = {
+            let s1 = Symbol::from("b");
+            let _s2 = s1.clone();
+            let s3 = Symbol::from("a");
+
+            let mut json = Vec::new();
+            export_json(&mut json).unwrap();
+            (json, (s3.id(), s1.id()))
+        };
+
+        let value: Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value["version"], json!(1));
+        assert_eq!(value["symbols"][1], json!({ "id": ids.0, "value": "a", "ref_count": 1 }));
+        assert_eq!(value["symbols"][2], json!({ "id": ids.1, "value": "b", "ref_count": 2 }));
+
+        let imported = import_json(json.as_slice()).unwrap();
+        assert_eq!(imported, ["", "a", "b"]);
+
+        let imported = import_json(r#"{ "symbols": ["x", { "value": "y", "tags": ["t"] }] }"#.as_bytes()).unwrap();
+        assert_eq!(imported, ["x", "y"]);
+
+        assert!(import_json(r#"{ "symbols": [1] }"#.as_bytes()).is_err());
+        assert!(import_json("[]".as_bytes()).is_err());
+    }
+}
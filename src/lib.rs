@@ -1,122 +1,771 @@
-#![feature(integer_atomics, allocator_api, alloc_layout_extra, slice_ptr_get)]
+#![cfg_attr(feature = "nightly", feature(allocator_api, slice_ptr_get))]
+// long-standing API and code style of collections, kept as is
+#![allow(clippy::len_without_is_empty, clippy::multiple_bound_locations, clippy::needless_borrowed_reference)]
 
 #[macro_use]
 extern crate lazy_static;
 
-use std::alloc::{Allocator, Global, Layout, handle_alloc_error};
+// allows `symbol!` expansions to refer to `::kg_symbol` within this crate
+#[cfg(all(test, feature = "macros"))]
+extern crate self as kg_symbol;
+
+#[cfg(feature = "global")]
+use std::alloc::{Layout, handle_alloc_error};
+#[cfg(feature = "global")]
 use std::borrow::{Borrow, Cow};
+#[cfg(feature = "global")]
 use std::cmp::Ordering;
-use std::collections::HashSet;
+#[cfg(feature = "global")]
+use std::ffi::OsStr;
+#[cfg(feature = "global")]
 use std::hash::{Hash, Hasher};
+#[cfg(feature = "global")]
 use std::ops::Deref;
+#[cfg(feature = "global")]
+use std::path::Path;
+#[cfg(feature = "global")]
 use std::ptr::NonNull;
-use std::sync::atomic::AtomicUsize;
-
-use parking_lot::Mutex;
-
+#[cfg(feature = "global")]
+use std::rc::Rc;
+#[cfg(feature = "global")]
+use std::sync::Arc;
+#[cfg(feature = "global")]
+use std::sync::atomic::{AtomicBool, AtomicU32};
+
+mod allocator;
+#[cfg(feature = "atom")]
+mod atom;
+#[cfg(feature = "global")]
+mod bimap;
+#[cfg(feature = "global")]
+mod btree;
+#[cfg(feature = "global")]
+mod buf;
+#[cfg(feature = "global")]
+mod bytes;
+#[cfg(feature = "thread-cache")]
+mod cache;
+#[cfg(feature = "global")]
+mod caseless;
+#[cfg(feature = "global")]
+mod codegen;
+#[cfg(feature = "collation")]
+mod collation;
+mod error;
+#[cfg(feature = "global")]
+mod freeze;
+#[cfg(feature = "global")]
+mod group;
+#[cfg(feature = "global")]
+mod fold;
+#[cfg(feature = "global")]
+mod format;
+mod hash;
+#[cfg(feature = "global")]
+mod hooks;
+#[cfg(feature = "global")]
+mod id;
+#[cfg(feature = "global")]
+mod ident;
+mod interner;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "global")]
+mod limits;
+#[cfg(feature = "global")]
 mod map;
-
+mod normalize;
+#[cfg(feature = "global")]
+mod path;
+#[cfg(feature = "global")]
+mod path_symbol;
+#[cfg(feature = "global")]
+mod phf;
+#[cfg(feature = "global")]
+mod qname;
+mod registry;
+#[cfg(feature = "global")]
+mod remap;
+#[cfg(feature = "global")]
+mod router;
+#[cfg(feature = "global")]
+mod scope;
+#[cfg(feature = "global")]
+mod set;
+#[cfg(feature = "global")]
+mod shard;
+#[cfg(feature = "global")]
+mod shutdown;
+#[cfg(feature = "global")]
+mod static_symbol;
+#[cfg(feature = "global")]
+mod stats;
+#[cfg(feature = "global")]
+mod store;
+#[cfg(feature = "global")]
+mod tuple;
+#[cfg(feature = "global")]
+mod unordered;
+#[cfg(feature = "global")]
+mod util;
+#[cfg(feature = "global")]
+mod weak;
+
+pub use self::allocator::*;
+#[cfg(feature = "atom")]
+pub use self::atom::*;
+#[cfg(feature = "global")]
+pub use self::bimap::*;
+#[cfg(feature = "global")]
+pub use self::btree::*;
+#[cfg(feature = "global")]
+pub use self::bytes::*;
+#[cfg(feature = "thread-cache")]
+pub use self::cache::*;
+#[cfg(feature = "global")]
+pub use self::caseless::*;
+#[cfg(feature = "global")]
+pub use self::codegen::*;
+#[cfg(feature = "collation")]
+pub use self::collation::*;
+pub use self::error::*;
+#[cfg(feature = "global")]
+pub use self::freeze::*;
+#[cfg(feature = "global")]
+pub use self::group::*;
+pub use self::hash::*;
+#[cfg(feature = "global")]
+pub use self::hooks::*;
+#[cfg(feature = "global")]
+pub use self::ident::*;
+pub use self::interner::*;
+#[cfg(feature = "json")]
+pub use self::json::*;
+#[cfg(feature = "global")]
+pub use self::limits::*;
+#[cfg(feature = "global")]
 pub use self::map::*;
-
+pub use self::normalize::*;
+#[cfg(feature = "global")]
+pub use self::path::*;
+#[cfg(feature = "global")]
+pub use self::path_symbol::*;
+#[cfg(feature = "global")]
+pub use self::phf::*;
+#[cfg(feature = "global")]
+pub use self::qname::*;
+pub use self::registry::*;
+#[cfg(feature = "global")]
+pub use self::remap::*;
+#[cfg(feature = "global")]
+pub use self::router::*;
+#[cfg(feature = "global")]
+pub use self::scope::*;
+#[cfg(feature = "global")]
+pub use self::set::*;
+#[cfg(feature = "global")]
+pub use self::shard::*;
+#[cfg(feature = "global")]
+pub use self::shutdown::*;
+#[cfg(feature = "global")]
+pub use self::static_symbol::*;
+#[cfg(feature = "global")]
+pub use self::stats::*;
+#[cfg(feature = "global")]
+pub use self::store::*;
+#[cfg(feature = "global")]
+pub use self::tuple::*;
+#[cfg(feature = "global")]
+pub use self::unordered::*;
+#[cfg(feature = "global")]
+pub use self::util::*;
+#[cfg(feature = "global")]
+pub use self::weak::*;
+
+#[cfg(feature = "global")]
 lazy_static!{
-    static ref SYMBOLS: Mutex<HashSet<Symbol>> = {
-        let mut set = HashSet::new();
-        set.insert(Symbol::alloc("", true));
-        Mutex::new(set)
+    static ref SYMBOLS: SymbolShards = {
+        let shards = SymbolShards::new();
+        shards.shard("").write().insert(Symbol::alloc("", true));
+        shards
     };
+
+    static ref EMPTY: Symbol = SYMBOLS.shard("").read().get("").cloned().unwrap();
 }
 
 
+/// Generation of the global symbol table, advanced whenever the table is reset.
+/// Symbols record generation they were created in, and in debug builds every access validates it,
+/// so that handles outliving their table are detected instead of aliasing freed memory.
+#[cfg(all(debug_assertions, feature = "global"))]
+static GENERATION: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(1);
+
+/// Generation tag written into headers of destroyed symbols.
+#[cfg(all(debug_assertions, feature = "global"))]
+const DESTROYED_GENERATION: usize = 0;
+
+/// Soft limit of reference counts. Exceeding it aborts the process, like `Arc` does, since
+/// the count could otherwise wrap around (e.g. with handles leaked by `mem::forget`) and
+/// the symbol would be freed while still in use. The margin up to `u32::MAX` leaves room
+/// for threads racing past the check.
+#[cfg(feature = "global")]
+const MAX_REF_COUNT: u32 = i32::MAX as u32;
+
+#[cfg(feature = "global")]
+#[cold]
+#[inline(never)]
+fn ref_count_overflow() -> ! {
+    std::process::abort()
+}
+
+
+/// Symbol header, followed directly by the string bytes.
+#[cfg(feature = "global")]
 struct SymbolHdr {
-    ref_count: AtomicUsize,
-    ptr: NonNull<u8>,
-    len: usize,
+    ref_count: AtomicU32,
+    /// Number of weak handles, plus one held collectively by all strong handles.
+    weak_count: AtomicU32,
+    /// Permanent symbols are never destroyed, and their handles do not update the reference count.
+    permanent: AtomicBool,
+    /// Pinned symbols are permanent symbols held by process-lifetime statics, never freed by [`shutdown`].
+    pinned: AtomicBool,
+    len: u32,
+    char_len: u32,
+    /// [`BuildSymbolHasher`] hash of the string, computed once at allocation.
+    hash: u64,
+    /// Hash of the string used by the global symbol table, see [`set_table_hasher`].
+    table_hash: u64,
+    id: u32,
+    #[cfg(feature = "origin-tracking")]
+    origin: &'static std::panic::Location<'static>,
+    #[cfg(debug_assertions)]
+    generation: usize,
+    #[cfg(feature = "casefold")]
+    folded: std::sync::atomic::AtomicPtr<u8>,
+    #[cfg(feature = "casefold")]
+    lower: std::sync::atomic::AtomicPtr<u8>,
 }
 
+#[cfg(feature = "global")]
 impl AsRef<str> for SymbolHdr {
     fn as_ref(&self) -> &str {
         unsafe {
-            std::str::from_utf8_unchecked(std::slice::from_raw_parts(self.ptr.as_ptr(), self.len))
+            let ptr = (self as *const SymbolHdr as *const u8).add(std::mem::size_of::<SymbolHdr>());
+            std::str::from_utf8_unchecked(std::slice::from_raw_parts(ptr, self.len as usize))
         }
     }
 }
 
 
+#[cfg(feature = "global")]
 #[inline]
 fn layout_offset(len: usize) -> (Layout, usize) {
-    unsafe {
-        Layout::new::<SymbolHdr>().extend(Layout::from_size_align_unchecked(len, 1)).unwrap()
+    try_layout_offset(len).expect("symbol layout overflow")
+}
+
+#[cfg(feature = "global")]
+#[inline]
+fn try_layout_offset(len: usize) -> Result<(Layout, usize), Error> {
+    if len > u32::MAX as usize {
+        return Err(Error::CapacityOverflow);
     }
+    let data = Layout::array::<u8>(len).map_err(|_| Error::CapacityOverflow)?;
+    Layout::new::<SymbolHdr>().extend(data).map_err(|_| Error::CapacityOverflow)
 }
 
 
+/// Raw symbol pointer, as seen from C code.
+///
+/// Headers generated by cbindgen should declare symbol fields using this type,
+/// treating it as an opaque, non-null handle.
+#[cfg(feature = "global")]
+pub type RawSymbol = *const u8;
+
+/// Reference-counted handle to an interned string.
+///
+/// `Symbol` is `#[repr(transparent)]` over a non-null pointer, so it has the same size,
+/// alignment and ABI as [`RawSymbol`], and `Option<Symbol>` is ABI-compatible with a nullable
+/// [`RawSymbol`]. It can be safely embedded in `#[repr(C)]` structs shared with C or C++ code.
+///
+/// Interned strings are never relocated, so there is no way to compact symbol memory after
+/// churn. Symbol ids (see [`Symbol::id`]) would provide the needed indirection, but a `&str`
+/// borrowed from one handle is not tied to any lock, so another thread could still move
+/// the string from under it; memory of destroyed symbols is reused by the allocator instead.
+#[cfg(feature = "global")]
+#[repr(transparent)]
 pub struct Symbol(NonNull<u8>);
 
+#[cfg(feature = "global")]
 impl Symbol {
+    /// Returns the persistent empty symbol, without locking the global symbol table.
+    #[inline]
+    pub fn empty() -> Symbol {
+        EMPTY.clone()
+    }
+
     #[inline(never)]
     pub fn get<S: AsRef<str>>(value: S) -> Option<Symbol> {
-        let symbols = SYMBOLS.lock();
-        symbols.get(value.as_ref()).cloned()
+        let value = normalize(value.as_ref());
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("lookup", value = &*value).entered();
+        if let Some(s) = lookup_static_symbol(&value) {
+            return Some(s);
+        }
+        if let Some(f) = frozen() {
+            return f.get(&value);
+        }
+        let symbols = SYMBOLS.shard(&value).read();
+        symbols.get(value.as_ref()).and_then(Symbol::upgrade)
     }
 
     #[inline(never)]
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
     pub fn new<S: AsRef<str>>(value: S) -> Symbol {
-        let mut symbols = SYMBOLS.lock();
+        let value = normalize(value.as_ref());
         let value = value.as_ref();
-        match symbols.get(value) {
-            Some(s) => s.clone(),
-            None => {
-                let s = Symbol::alloc(value, false);
-                let p = s.0;
-                symbols.insert(s);
-                Symbol(p)
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("intern", value).entered();
+        if let Some(s) = lookup_static_symbol(value).or_else(|| frozen().and_then(|f| f.get(value))) {
+            return s;
+        }
+        let hash = std::hash::BuildHasher::hash_one(&BuildSymbolHasher::default(), value);
+        #[cfg(feature = "thread-cache")]
+        {
+            if let Some(s) = cache::get(value, hash) {
+                #[cfg(feature = "tracing")]
+                tracing::trace!("thread cache hit");
+                return s;
             }
         }
+        let shard = SYMBOLS.shard_hashed(hash);
+        let found = shard.read().get(value).and_then(Symbol::upgrade);
+        let s = match found {
+            Some(s) => s,
+            // symbol might have been created after releasing the read lock
+            None => Symbol::intern(&mut shard.write(), value),
+        };
+        #[cfg(feature = "thread-cache")]
+        cache::insert(hash, &s);
+        s
+    }
+
+    /// Same as [`Symbol::new`], but returns an error instead of panicking or aborting
+    /// when symbol allocation fails.
+    #[inline(never)]
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    pub fn try_new<S: AsRef<str>>(value: S) -> Result<Symbol, Error> {
+        let value = normalize(value.as_ref());
+        let value = value.as_ref();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("intern", value).entered();
+        if let Some(s) = lookup_static_symbol(value).or_else(|| frozen().and_then(|f| f.get(value))) {
+            return Ok(s);
+        }
+        let shard = SYMBOLS.shard(value);
+        if let Some(s) = shard.read().get(value).and_then(Symbol::upgrade) {
+            return Ok(s);
+        }
+
+        Symbol::try_intern(&mut shard.write(), value)
+    }
+
+    /// Interns UTF-8 encoded `bytes`, returning an error if they are not valid UTF-8.
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    pub fn from_utf8(bytes: &[u8]) -> Result<Symbol, std::str::Utf8Error> {
+        Ok(Symbol::new(std::str::from_utf8(bytes)?))
+    }
+
+    /// Interns UTF-8 encoded `bytes`, replacing invalid sequences with `U+FFFD`.
+    /// Valid input is interned without copying it to an intermediate `String`.
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    pub fn from_utf8_lossy(bytes: &[u8]) -> Symbol {
+        Symbol::new(String::from_utf8_lossy(bytes))
+    }
+
+    /// Same as [`Symbol::new`], but uses precomputed [`BuildSymbolHasher`] hash of `value`
+    /// to select the table shard.
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    pub(crate) fn new_hashed(value: &str, hash: u64) -> Symbol {
+        debug_assert_eq!(hash, std::hash::BuildHasher::hash_one(&BuildSymbolHasher::default(), value));
+        let normalized = normalize(value);
+        if normalized != value {
+            return Symbol::new(normalized);
+        }
+        if let Some(s) = lookup_static_symbol(value).or_else(|| frozen().and_then(|f| f.get(value))) {
+            return s;
+        }
+        let shard = SYMBOLS.shard_hashed(hash);
+        if let Some(s) = shard.read().get(value).and_then(Symbol::upgrade) {
+            return s;
+        }
+
+        Symbol::intern(&mut shard.write(), value)
+    }
+
+    /// Interns all strings from `values`, locking each shard of the symbol table at most once.
+    /// Returned symbols are in the same order as `values`.
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    pub fn new_all<I, S>(values: I) -> Vec<Symbol>
+        where I: IntoIterator<Item = S>, S: AsRef<str>
+    {
+        let values: Vec<S> = values.into_iter().collect();
+        let values: Vec<_> = values.iter().map(|v| normalize(v.as_ref())).collect();
+        SYMBOLS.intern_all(&values)
     }
 
+    /// Interns `value` and makes the symbol permanent, see [`Symbol::make_permanent`].
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    pub fn permanent<S: AsRef<str>>(value: S) -> Symbol {
+        let s = Symbol::new(value);
+        s.make_permanent();
+        s
+    }
+
+    /// Pins symbol for the lifetime of the process. Permanent symbols are never deallocated,
+    /// and cloning or dropping them does not update the reference count.
+    pub fn make_permanent(&self) {
+        use std::sync::atomic::Ordering;
+
+        let hdr = self.header();
+        if !hdr.permanent.load(Ordering::Acquire) {
+            // reference owned by the symbol itself, never released
+            if hdr.ref_count.fetch_add(1, Ordering::Relaxed) > MAX_REF_COUNT {
+                ref_count_overflow();
+            }
+            if hdr.permanent.swap(true, Ordering::AcqRel) {
+                hdr.ref_count.fetch_sub(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Makes the symbol permanent for a process-lifetime static, so that [`shutdown`] keeps it.
+    pub(crate) fn pin(&self) {
+        self.make_permanent();
+        self.header().pinned.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Makes the symbol permanent and returns its string, which is never deallocated.
+    pub fn leak(self) -> &'static str {
+        self.make_permanent();
+        // permanent symbols are never deallocated
+        unsafe { &*(self.as_str() as *const str) }
+    }
+
+    pub fn is_permanent(&self) -> bool {
+        self.header().permanent.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Same as [`Symbol::new`], but also returns `true` if the symbol was newly created,
+    /// or `false` if `value` was already interned.
+    #[inline(never)]
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    pub fn intern_with_status<S: AsRef<str>>(value: S) -> (Symbol, bool) {
+        let value = normalize(value.as_ref());
+        let value = value.as_ref();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("intern", value).entered();
+        if let Some(s) = lookup_static_symbol(value).or_else(|| frozen().and_then(|f| f.get(value))) {
+            return (s, false);
+        }
+        let shard = SYMBOLS.shard(value);
+        if let Some(s) = shard.read().get(value).and_then(Symbol::upgrade) {
+            return (s, false);
+        }
+
+        Symbol::unwrap_interned(Symbol::try_intern_with_status(&mut shard.write(), value), value)
+    }
+
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    fn intern(symbols: &mut SymbolTable, value: &str) -> Symbol {
+        Symbol::unwrap_interned(Symbol::try_intern(symbols, value), value)
+    }
+
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    fn unwrap_interned<T>(res: Result<T, Error>, value: &str) -> T {
+        match res {
+            Ok(s) => s,
+            Err(Error::Alloc(_)) => handle_alloc_error(layout_offset(value.len()).0),
+            Err(err) => panic!("cannot intern symbol: {}", err),
+        }
+    }
+
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    fn try_intern(symbols: &mut SymbolTable, value: &str) -> Result<Symbol, Error> {
+        Symbol::try_intern_with_status(symbols, value).map(|(s, _)| s)
+    }
+
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    fn try_intern_with_status(symbols: &mut SymbolTable, value: &str) -> Result<(Symbol, bool), Error> {
+        if let Some(f) = frozen() {
+            return f.try_get(value).map(|s| (s, false));
+        }
+        if let Some(s) = symbols.get(value) {
+            if let Some(s) = s.upgrade() {
+                return Ok((s, false));
+            }
+            // last handle was released, but the symbol is not destroyed yet
+            std::mem::forget(symbols.take(value));
+        }
+        check_limits(value)?;
+        let s = Symbol::try_alloc(value, false)?;
+        scope::record(&s);
+        let p = s.0;
+        symbols.insert(s);
+        hooks::interned(value);
+        #[cfg(feature = "tracing")]
+        tracing::trace!("symbol created");
+        Ok((Symbol(p), true))
+    }
+
+    /// Clones symbol stored in the table, unless its last handle has already been released
+    /// and it is about to be destroyed. Symbols must never be cloned from the table otherwise.
+    #[inline]
+    fn upgrade(&self) -> Option<Symbol> {
+        unsafe { Symbol::upgrade_raw(self.0) }
+    }
+
+    /// Same as [`Symbol::upgrade`], for pointers to symbols which might already be destroyed,
+    /// but not yet deallocated.
+    unsafe fn upgrade_raw(p: NonNull<u8>) -> Option<Symbol> {
+        use std::sync::atomic::Ordering;
+
+        let hdr = &*(p.as_ptr() as *const SymbolHdr);
+        if hdr.permanent.load(Ordering::Relaxed) {
+            return Some(Symbol(p));
+        }
+        let ref_count = &hdr.ref_count;
+        let mut n = ref_count.load(Ordering::Relaxed);
+        loop {
+            if n == 0 {
+                return None;
+            }
+            if n > MAX_REF_COUNT {
+                ref_count_overflow();
+            }
+            match ref_count.compare_exchange_weak(n, n + 1, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => return Some(Symbol(p)),
+                Err(c) => n = c,
+            }
+        }
+    }
+
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
     fn alloc(value: &str, persistent: bool) -> Symbol {
-        let (layout, offset) = layout_offset(value.len());
+        match Symbol::try_alloc(value, persistent) {
+            Ok(s) => s,
+            Err(Error::Alloc(_)) => handle_alloc_error(layout_offset(value.len()).0),
+            Err(err) => panic!("cannot intern symbol: {}", err),
+        }
+    }
+
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    fn try_alloc(value: &str, persistent: bool) -> Result<Symbol, Error> {
+        let (layout, offset) = try_layout_offset(value.len())?;
+        debug_assert_eq!(offset, std::mem::size_of::<SymbolHdr>());
+        let data = allocator::allocate(layout).ok_or(AllocError)?;
+        let id = match id::reserve_id() {
+            Ok(id) => id,
+            Err(err) => {
+                unsafe { allocator::deallocate(data, layout) };
+                return Err(err);
+            }
+        };
+        let hash = std::hash::BuildHasher::hash_one(&BuildSymbolHasher::default(), value);
         let p = unsafe {
-            let data = Global.allocate(layout).unwrap_or_else(|_| handle_alloc_error(layout));
-            let str_ptr = data.as_non_null_ptr().as_ptr().offset(offset as isize);
-            let hdr_ptr = std::mem::transmute::<NonNull<u8>, &mut SymbolHdr>(data.as_non_null_ptr());
+            let str_ptr = data.as_ptr().add(offset);
+            let hdr_ptr = std::mem::transmute::<NonNull<u8>, &mut SymbolHdr>(data);
             *hdr_ptr = SymbolHdr {
-                ref_count: AtomicUsize::new(if persistent { 2 } else { 1 }),
-                ptr: NonNull::new_unchecked(str_ptr),
-                len: value.len(),
+                ref_count: AtomicU32::new(if persistent { 2 } else { 1 }),
+                weak_count: AtomicU32::new(1),
+                permanent: AtomicBool::new(persistent),
+                pinned: AtomicBool::new(persistent),
+                len: value.len() as u32,
+                char_len: value.chars().count() as u32,
+                hash,
+                table_hash: shard::table_hash(value),
+                id,
+                #[cfg(feature = "origin-tracking")]
+                origin: std::panic::Location::caller(),
+                #[cfg(debug_assertions)]
+                generation: GENERATION.load(std::sync::atomic::Ordering::Relaxed),
+                #[cfg(feature = "casefold")]
+                folded: std::sync::atomic::AtomicPtr::new(std::ptr::null_mut()),
+                #[cfg(feature = "casefold")]
+                lower: std::sync::atomic::AtomicPtr::new(std::ptr::null_mut()),
             };
             std::ptr::copy_nonoverlapping(value.as_ptr(), str_ptr, value.len());
-            data.as_non_null_ptr()
+            data
         };
-        Symbol(p)
+        id::bind_id(id, p, hash);
+        stats::allocated(allocator::allocated_size(layout));
+        Ok(Symbol(p))
     }
 
     #[inline(never)]
     fn destroy(&mut self) {
-        let mut symbols = SYMBOLS.lock();
-        symbols.remove(self);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("free", value = self.as_str()).entered();
+        let mut symbols = SYMBOLS.shard_hashed(self.header().hash).write();
+        // entry might have been replaced by a new symbol after the reference count dropped to zero,
+        // table entries do not own a reference, so they must not be dropped
+        if symbols.get(self.as_str()).is_some_and(|s| s.0 == self.0) {
+            std::mem::forget(symbols.take(self.as_str()));
+        }
+        hooks::freed(self.as_str());
+        stats::destroyed();
+        #[cfg(feature = "casefold")]
+        let companions = fold::take_companions(self);
+        id::release_id(self.header().id);
 
-        let (layout, _) = layout_offset(self.header().len);
         unsafe {
-            Global.deallocate(self.0, layout);
+            Symbol::release_weak(self.0);
+        }
+
+        // case siblings must be released after the table lock, since they may be destroyed as well
+        #[cfg(feature = "casefold")]
+        {
+            drop(symbols);
+            drop(companions);
         }
     }
 
+    /// Releases a weak reference, deallocating the symbol when it was the last one.
+    unsafe fn release_weak(p: NonNull<u8>) {
+        use std::sync::atomic::{fence, Ordering};
+
+        let hdr = p.as_ptr() as *mut SymbolHdr;
+        if (*hdr).weak_count.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+        fence(Ordering::Acquire);
+
+        let (layout, _) = layout_offset((*hdr).len as usize);
+        #[cfg(debug_assertions)]
+        {
+            (*hdr).generation = DESTROYED_GENERATION;
+        }
+        allocator::deallocate(p, layout);
+        stats::deallocated(allocator::allocated_size(layout));
+    }
+
     #[inline(always)]
     fn header(&self) -> &SymbolHdr {
-        unsafe { std::mem::transmute::<NonNull<u8>, &SymbolHdr>(self.0) }
+        let hdr = unsafe { std::mem::transmute::<NonNull<u8>, &SymbolHdr>(self.0) };
+        #[cfg(debug_assertions)]
+        Symbol::check_generation(hdr.generation);
+        hdr
+    }
+
+    #[cfg(debug_assertions)]
+    #[inline]
+    fn check_generation(generation: usize) {
+        if generation == DESTROYED_GENERATION {
+            panic!("use of destroyed symbol");
+        }
+        let current = GENERATION.load(std::sync::atomic::Ordering::Relaxed);
+        if generation != current {
+            panic!("use of symbol from table generation {}, current generation is {}", generation, current);
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        self.header().as_ref()
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.as_str().as_bytes()
+    }
+
+    /// Copies symbol string into a new `Arc<str>`.
+    pub fn to_arc_str(&self) -> Arc<str> {
+        Arc::from(self.as_str())
+    }
+
+    /// Copies symbol string into a new `Rc<str>`.
+    pub fn to_rc_str(&self) -> Rc<str> {
+        Rc::from(self.as_str())
+    }
+
+    /// Returns [`BuildSymbolHasher`] hash of the string, computed once when the symbol was created.
+    ///
+    /// Note that [`Hash`] for `Symbol` still hashes the string, since it must be consistent
+    /// with `Hash` for `str` for lookups by `&str` to work.
+    #[inline]
+    pub fn precomputed_hash(&self) -> u64 {
+        self.header().hash
+    }
+
+    /// Returns number of characters in the symbol, in constant time.
+    #[inline]
+    pub fn char_len(&self) -> usize {
+        self.header().char_len as usize
+    }
+
+    /// Returns symbol with at most `n` first characters of this symbol.
+    pub fn truncate_chars(&self, n: usize) -> Symbol {
+        if n >= self.char_len() {
+            return self.clone();
+        }
+        match self.char_indices().nth(n) {
+            Some((i, _)) => Symbol::new(&self[..i]),
+            None => self.clone(),
+        }
+    }
+
+    /// Returns symbol with at most `n` first bytes of this symbol,
+    /// shortened further if needed to not split a multi-byte character.
+    pub fn truncate_bytes_lossy(&self, n: usize) -> Symbol {
+        if n >= self.len() {
+            return self.clone();
+        }
+        let mut i = n;
+        while !self.is_char_boundary(i) {
+            i -= 1;
+        }
+        Symbol::new(&self[..i])
+    }
+
+    /// Returns location of the call which first interned this symbol.
+    #[cfg(feature = "origin-tracking")]
+    pub fn origin(&self) -> &'static std::panic::Location<'static> {
+        self.header().origin
     }
 
-    #[cfg(test)]
     fn ref_count(&self) -> usize {
-        self.header().ref_count.load(std::sync::atomic::Ordering::SeqCst)
+        self.header().ref_count.load(std::sync::atomic::Ordering::SeqCst) as usize
+    }
+
+    /// Consumes the symbol, returning its raw pointer without decrementing the reference count.
+    pub fn into_raw(self) -> RawSymbol {
+        let p = self.0.as_ptr();
+        std::mem::forget(self);
+        p
+    }
+
+    /// Reconstructs symbol from a raw pointer previously returned by [`Symbol::into_raw`].
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must come from [`Symbol::into_raw`], and each pointer can be converted back only once.
+    pub unsafe fn from_raw(ptr: RawSymbol) -> Symbol {
+        Symbol(NonNull::new_unchecked(ptr as *mut u8))
+    }
+
+    /// Returns adapter for debug output including symbol address and current reference count.
+    pub fn debug_identity(&self) -> DebugIdentity<'_> {
+        DebugIdentity(self)
     }
 }
 
+#[cfg(feature = "global")]
 impl Drop for Symbol {
     #[inline(always)]
     fn drop(&mut self) {
-        if self.header().ref_count.fetch_sub(1, std::sync::atomic::Ordering::Release) != 1 {
+        let hdr = self.header();
+        if hdr.permanent.load(std::sync::atomic::Ordering::Relaxed)
+            || hdr.ref_count.fetch_sub(1, std::sync::atomic::Ordering::Release) != 1 {
             return;
         }
 
@@ -126,190 +775,344 @@ impl Drop for Symbol {
     }
 }
 
+#[cfg(feature = "global")]
 impl Clone for Symbol {
     #[inline(always)]
     fn clone(&self) -> Self {
-        self.header().ref_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let hdr = self.header();
+        if !hdr.permanent.load(std::sync::atomic::Ordering::Relaxed)
+            && hdr.ref_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed) > MAX_REF_COUNT {
+            ref_count_overflow();
+        }
         Symbol(self.0)
     }
 }
 
+#[cfg(feature = "global")]
 impl AsRef<str> for Symbol {
     fn as_ref(&self) -> &str {
-        self.header().as_ref()
+        self.as_str()
+    }
+}
+
+#[cfg(feature = "global")]
+impl AsRef<[u8]> for Symbol {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+#[cfg(feature = "global")]
+impl AsRef<OsStr> for Symbol {
+    fn as_ref(&self) -> &OsStr {
+        self.as_str().as_ref()
+    }
+}
+
+#[cfg(feature = "global")]
+impl AsRef<Path> for Symbol {
+    fn as_ref(&self) -> &Path {
+        self.as_str().as_ref()
     }
 }
 
+#[cfg(feature = "global")]
 impl Deref for Symbol {
     type Target = str;
 
     fn deref(&self) -> &Self::Target {
-        self.as_ref()
+        self.as_str()
     }
 }
 
+#[cfg(feature = "global")]
 impl Borrow<str> for Symbol {
     fn borrow(&self) -> &str {
-        self.as_ref()
+        self.as_str()
     }
 }
 
+#[cfg(feature = "global")]
 impl PartialEq for Symbol {
     fn eq(&self, other: &Symbol) -> bool {
         self.0 == other.0
     }
 }
 
+#[cfg(feature = "global")]
 impl Eq for Symbol {}
 
+#[cfg(feature = "global")]
 impl PartialOrd for Symbol {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
+#[cfg(feature = "global")]
 impl Ord for Symbol {
     fn cmp(&self, other: &Self) -> Ordering {
         if self.eq(other) {
             Ordering::Equal
         } else {
-            self.as_ref().cmp(&other.as_ref())
+            self.as_str().cmp(other.as_str())
         }
     }
 }
 
+#[cfg(feature = "global")]
 impl PartialEq<str> for Symbol {
     fn eq(&self, other: &str) -> bool {
-        self.as_ref() == other
+        self.as_str() == other
     }
 }
 
+#[cfg(feature = "global")]
 impl<'a> PartialEq<&'a str> for Symbol {
     fn eq(&self, other: &&'a str) -> bool {
-        self.as_ref() == *other
+        self.as_str() == *other
     }
 }
 
+#[cfg(feature = "global")]
 impl PartialEq<String> for Symbol {
     fn eq(&self, other: &String) -> bool {
-        self.as_ref() == other.as_str()
+        self.as_str() == other.as_str()
     }
 }
 
+#[cfg(feature = "global")]
 impl<'a> PartialEq<Cow<'a, str>> for Symbol {
     fn eq(&self, other: &Cow<'a, str>) -> bool {
-        self.as_ref() == other.as_ref()
+        self.as_str() == other.as_ref()
+    }
+}
+
+#[cfg(feature = "global")]
+impl PartialEq<[u8]> for Symbol {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_bytes() == other
     }
 }
 
+#[cfg(feature = "global")]
+impl<'a> PartialEq<&'a [u8]> for Symbol {
+    fn eq(&self, other: &&'a [u8]) -> bool {
+        self.as_bytes() == *other
+    }
+}
+
+#[cfg(feature = "global")]
 impl PartialOrd<str> for Symbol {
     fn partial_cmp(&self, other: &str) -> Option<Ordering> {
-        self.as_ref().partial_cmp(other)
+        self.as_str().partial_cmp(other)
     }
 }
 
+#[cfg(feature = "global")]
 impl<'a> PartialOrd<&'a str> for Symbol {
     fn partial_cmp(&self, other: &&'a str) -> Option<Ordering> {
-        self.as_ref().partial_cmp(*other)
+        self.as_str().partial_cmp(*other)
     }
 }
 
+#[cfg(feature = "global")]
 impl PartialOrd<String> for Symbol {
     fn partial_cmp(&self, other: &String) -> Option<Ordering> {
-        self.as_ref().partial_cmp(other.as_str())
+        self.as_str().partial_cmp(other.as_str())
     }
 }
 
+#[cfg(feature = "global")]
 impl<'a> PartialOrd<Cow<'a, str>> for Symbol {
     fn partial_cmp(&self, other: &Cow<'a, str>) -> Option<Ordering> {
-        self.as_ref().partial_cmp(other.as_ref())
+        self.as_str().partial_cmp(other.as_ref())
     }
 }
 
+#[cfg(feature = "global")]
 impl std::fmt::Debug for Symbol {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        std::fmt::Debug::fmt(self.as_ref(), f)
+        std::fmt::Debug::fmt(self.as_str(), f)
     }
 }
 
+#[cfg(feature = "global")]
 impl std::fmt::Display for Symbol {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        std::fmt::Display::fmt(self.as_ref(), f)
+        std::fmt::Display::fmt(self.as_str(), f)
     }
 }
 
+#[cfg(feature = "global")]
+pub struct DebugIdentity<'a>(&'a Symbol);
+
+#[cfg(feature = "global")]
+impl<'a> std::fmt::Debug for DebugIdentity<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Symbol")
+            .field("value", &self.0.as_str())
+            .field("ptr", &self.0.0)
+            .field("ref_count", &self.0.ref_count())
+            .finish()
+    }
+}
+
+#[cfg(feature = "global")]
+/// Symbols are hashed by their strings rather than by the precomputed hash, since `Symbol`
+/// implements `Borrow<str>` and maps keyed by symbols must find them by `&str` as well.
+/// Use [`IdentityKey`] for keys hashed in constant time.
 impl Hash for Symbol {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.as_ref().hash(state)
+        self.as_str().hash(state)
     }
 }
 
+#[cfg(feature = "global")]
 impl Default for Symbol {
     fn default() -> Self {
-        Symbol::new("")
+        Symbol::empty()
     }
 }
 
+#[cfg(feature = "global")]
 impl<'a> From<&'a Symbol> for Symbol {
     fn from(s: &'a Symbol) -> Self {
         s.clone()
     }
 }
 
+#[cfg(feature = "global")]
 impl From<String> for Symbol {
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
     fn from(s: String) -> Self {
         Symbol::new(s)
     }
 }
 
+#[cfg(feature = "global")]
 impl<'a> From<&'a String> for Symbol {
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
     fn from(s: &'a String) -> Self {
         Symbol::new(s)
     }
 }
 
+#[cfg(feature = "global")]
 impl<'a> From<&'a str> for Symbol {
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
     fn from(s: &'a str) -> Self {
         Symbol::new(s)
     }
 }
 
+#[cfg(feature = "global")]
 impl<'a> From<Cow<'a, str>> for Symbol {
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
     fn from(s: Cow<'a, str>) -> Self {
         Symbol::new(s)
     }
 }
 
+#[cfg(feature = "global")]
 impl<'a, 'b> From<&'b Cow<'a, str>> for Symbol {
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
     fn from(s: &'b Cow<'a, str>) -> Self {
         Symbol::new(s)
     }
 }
 
+#[cfg(feature = "global")]
+impl From<Arc<str>> for Symbol {
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    fn from(s: Arc<str>) -> Self {
+        Symbol::new(s)
+    }
+}
+
+#[cfg(feature = "global")]
+impl From<Rc<str>> for Symbol {
+    #[cfg_attr(feature = "origin-tracking", track_caller)]
+    fn from(s: Rc<str>) -> Self {
+        Symbol::new(s)
+    }
+}
+
+#[cfg(feature = "global")]
+impl std::str::FromStr for Symbol {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Symbol::new(s))
+    }
+}
+
+/// Collects characters into a stack buffer (spilling to the heap for long strings) before interning.
+#[cfg(feature = "global")]
+impl std::iter::FromIterator<char> for Symbol {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut buf = buf::StrBuf::new();
+        iter.into_iter().for_each(|c| buf.push(c));
+        Symbol::new(buf.as_str())
+    }
+}
+
+/// Concatenates strings in a stack buffer (spilling to the heap for long strings) before interning.
+#[cfg(feature = "global")]
+impl<'a> std::iter::FromIterator<&'a str> for Symbol {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut buf = buf::StrBuf::new();
+        iter.into_iter().for_each(|s| buf.push_str(s));
+        Symbol::new(buf.as_str())
+    }
+}
+
+#[cfg(feature = "global")]
+impl From<Symbol> for Arc<str> {
+    fn from(s: Symbol) -> Self {
+        s.to_arc_str()
+    }
+}
+
+#[cfg(feature = "global")]
+impl From<Symbol> for Rc<str> {
+    fn from(s: Symbol) -> Self {
+        s.to_rc_str()
+    }
+}
+
+#[cfg(feature = "global")]
 impl heapsize::HeapSizeOf for Symbol {
     fn heap_size_of_children(&self) -> usize {
-        layout_offset(self.header().len).0.size()
+        allocator::allocated_size(layout_offset(self.header().len as usize).0)
     }
 }
 
+#[cfg(feature = "global")]
 impl serde::Serialize for Symbol {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
-        self.as_ref().serialize(serializer)
+        self.as_str().serialize(serializer)
     }
 }
 
+#[cfg(feature = "global")]
 impl<'de> serde::Deserialize<'de> for Symbol {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: serde::Deserializer<'de> {
-        Ok(Symbol::from(String::deserialize(deserializer)?))
+        use serde::de::Error;
+
+        let s = String::deserialize(deserializer)?;
+        Symbol::try_new(&s).map_err(D::Error::custom)
     }
 }
 
+#[cfg(feature = "global")]
 unsafe impl Send for Symbol {}
 
+#[cfg(feature = "global")]
 unsafe impl Sync for Symbol {}
 
 
-#[cfg(test)]
+#[cfg(all(test, feature = "global"))]
 mod tests {
     use parking_lot::{Mutex, MutexGuard};
     use super::*;
@@ -319,7 +1122,7 @@ mod tests {
 
     pub(crate) fn test_lock<'a>() -> MutexGuard<'a, ()> {
         let lock = TEST_LOCK.lock();
-        debug_assert_eq!(SYMBOLS.lock().len(), 1);
+        debug_assert_eq!(SYMBOLS.len(), 1);
         lock
     }
 
@@ -347,10 +1150,10 @@ mod tests {
             let s3 = Symbol::from("aaaa");
             assert_eq!(s2.ref_count(), 2);
             assert_eq!(s3.ref_count(), 1);
-            assert_eq!(SYMBOLS.lock().len(), 3);
+            assert_eq!(SYMBOLS.len(), 3);
         }
 
-        assert_eq!(SYMBOLS.lock().len(), 1);
+        assert_eq!(SYMBOLS.len(), 1);
     }
 
     #[test]
@@ -386,7 +1189,7 @@ mod tests {
 
         let json = "\"example\"";
         let s: Symbol = serde_json::from_str(json).unwrap();
-        assert_eq!(s.as_ref(), "example");
+        assert_eq!(s.as_str(), "example");
     }
 
     #[test]
@@ -398,6 +1201,83 @@ mod tests {
         test(Symbol::from("example"));
     }
 
+    #[test]
+    fn new_all_preserves_order() {
+        let _lock = test_lock();
+
+        let headers = vec!["id".to_string(), "name".to_string(), "id".to_string()];
+        let symbols = Symbol::new_all(&headers);
+        assert_eq!(symbols.len(), 3);
+        assert_eq!(symbols[0], "id");
+        assert_eq!(symbols[1], Symbol::new("name"));
+        assert_eq!(symbols[0], symbols[2]);
+        assert!(Symbol::new_all(Vec::<&str>::new()).is_empty());
+    }
+
+    #[test]
+    fn intern_with_status_reports_new_symbols() {
+        let _lock = test_lock();
+
+        let (s1, created1) = Symbol::intern_with_status("first seen");
+        let (s2, created2) = Symbol::intern_with_status("first seen");
+        assert!(created1);
+        assert!(!created2);
+        assert_eq!(s1, s2);
+        drop((s1, s2));
+        assert!(Symbol::intern_with_status("first seen").1);
+    }
+
+    #[test]
+    fn permanent_symbols_are_not_reclaimed() {
+        let _lock = test_lock();
+
+        let s = Symbol::new("keyword");
+        assert!(!s.is_permanent());
+        s.make_permanent();
+        s.make_permanent();
+        let rc = s.ref_count();
+        let s2 = s.clone();
+        assert_eq!(s.ref_count(), rc);
+        drop((s, s2));
+        assert!(Symbol::get("keyword").unwrap().is_permanent());
+        assert!(Symbol::empty().is_permanent());
+        assert!(Symbol::permanent("other keyword").is_permanent());
+
+        let leaked: &'static str = Symbol::new("leaked keyword").leak();
+        assert_eq!(leaked, "leaked keyword");
+        assert!(Symbol::get(leaked).unwrap().is_permanent());
+
+        // permanent symbols outlive the test, remove them from the table to keep it clean for other tests
+        for v in ["keyword", "other keyword", "leaked keyword"].iter() {
+            let mut shard = SYMBOLS.shard(v).write();
+            std::mem::forget(shard.take(v));
+        }
+    }
+
+    #[test]
+    fn concurrent_drop_and_lookup_do_not_resurrect() {
+        let _lock = test_lock();
+
+        let threads: Vec<_> = (0..8).map(|i| {
+            std::thread::spawn(move || {
+                for _ in 0..10_000 {
+                    let s = Symbol::new("contended");
+                    if i % 2 == 0 {
+                        assert_eq!(Symbol::get("contended").as_ref(), Some(&s));
+                    }
+                    drop(s);
+                    drop(Symbol::get("contended"));
+                }
+            })
+        }).collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        assert_eq!(Symbol::get("contended"), None);
+        assert_eq!(SYMBOLS.len(), 1);
+    }
+
     #[test]
     fn symbol_is_send() {
         let _lock = test_lock();
@@ -430,18 +1310,228 @@ mod tests {
         assert_eq!(h1, h2);
     }
 
+    #[test]
+    fn debug_identity_output() {
+        let _lock = test_lock();
+
+        let s1 = Symbol::from("example");
+        let _s2 = s1.clone();
+
+        assert_eq!(format!("{:?}", s1), "\"example\"");
+        assert_eq!(format!("{:?}", s1.debug_identity()),
+            format!("Symbol {{ value: \"example\", ptr: {:?}, ref_count: 2 }}", s1.0));
+    }
+
+    #[cfg(feature = "origin-tracking")]
+    #[test]
+    fn origin_is_first_interning_call_site() {
+        let _lock = test_lock();
+
+        let line = line!() + 1;
+        let s1 = Symbol::from("example");
+        let s2 = Symbol::new("example");
+
+        assert_eq!(s1.origin().file(), file!());
+        assert_eq!(s1.origin().line(), line);
+        assert_eq!(s2.origin(), s1.origin());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn interner_operations_are_traced() {
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        #[derive(Default, Clone)]
+        struct SpanNames(Arc<Mutex<Vec<&'static str>>>);
+
+        impl Subscriber for SpanNames {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                let mut names = self.0.lock();
+                names.push(span.metadata().name());
+                Id::from_u64(names.len() as u64)
+            }
+
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, _: &Event<'_>) {}
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let _lock = test_lock();
+
+        let names = SpanNames::default();
+        tracing::subscriber::with_default(names.clone(), || {
+            let s = Symbol::new("traced");
+            assert_eq!(Symbol::get("traced"), Some(s));
+        });
+
+        assert_eq!(*names.0.lock(), ["intern", "lookup", "free"]);
+    }
+
+    #[test]
+    fn empty_symbol_is_interned_empty_string() {
+        let _lock = test_lock();
+
+        let s1 = Symbol::empty();
+        let s2 = Symbol::default();
+        let s3 = Symbol::new("");
+
+        assert_eq!(s1.0, s3.0);
+        assert_eq!(s2.0, s3.0);
+        assert_eq!(s1, "");
+    }
+
+    #[test]
+    fn char_len_counts_characters() {
+        let _lock = test_lock();
+
+        assert_eq!(Symbol::empty().char_len(), 0);
+        assert_eq!(Symbol::from("abc").char_len(), 3);
+        assert_eq!(Symbol::from("zażółć").char_len(), 6);
+        assert_eq!(Symbol::from("zażółć").len(), 10);
+    }
+
+    #[test]
+    fn truncation_respects_char_boundaries() {
+        let _lock = test_lock();
+
+        let s = Symbol::from("zażółć");
+
+        assert_eq!(s.truncate_chars(3), "zaż");
+        assert_eq!(s.truncate_chars(0), "");
+        assert_eq!(s.truncate_chars(6).0, s.0);
+        assert_eq!(s.truncate_chars(100).0, s.0);
+        assert_eq!(s.truncate_bytes_lossy(3), "za");
+        assert_eq!(s.truncate_bytes_lossy(4), "zaż");
+        assert_eq!(s.truncate_bytes_lossy(100).0, s.0);
+    }
+
+    #[test]
+    fn from_utf8_bytes() {
+        let _lock = test_lock();
+
+        assert_eq!(Symbol::from_utf8(b"header").unwrap(), "header");
+        assert!(Symbol::from_utf8(b"bad\xff").is_err());
+        assert_eq!(Symbol::from_utf8_lossy(b"header"), Symbol::new("header"));
+        assert_eq!(Symbol::from_utf8_lossy(b"bad\xff"), "bad\u{FFFD}");
+    }
+
+    #[test]
+    fn parse_and_collect() {
+        let _lock = test_lock();
+
+        let s: Symbol = "parsed".parse().unwrap();
+        assert_eq!(s, "parsed");
+        assert_eq!("parsed".chars().collect::<Symbol>(), s);
+        assert_eq!(["par", "", "sed"].iter().copied().collect::<Symbol>(), s);
+        assert_eq!(std::iter::empty::<char>().collect::<Symbol>(), Symbol::empty());
+
+        let long: Symbol = std::iter::repeat_n("long ", 20).collect();
+        assert_eq!(long, "long ".repeat(20));
+    }
+
+    #[test]
+    fn byte_comparisons() {
+        let _lock = test_lock();
+
+        let s = Symbol::from("GET");
+        let wire: &[u8] = b"GET /index.html";
+
+        assert_eq!(s.as_bytes(), b"GET");
+        assert_eq!(AsRef::<[u8]>::as_ref(&s), b"GET");
+        assert_eq!(AsRef::<OsStr>::as_ref(&s), "GET");
+        assert_eq!(AsRef::<Path>::as_ref(&s), Path::new("GET"));
+        let method = &wire[..3];
+
+        assert!(s == *method);
+        assert!(s == method);
+        assert!(s != wire);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn stale_generation_is_detected() {
+        use std::sync::atomic::Ordering;
+
+        let _lock = test_lock();
+
+        let s = Symbol::new("stale");
+        GENERATION.fetch_add(1, Ordering::SeqCst);
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| s.as_str().len()));
+        GENERATION.fetch_sub(1, Ordering::SeqCst);
+
+        assert!(res.is_err());
+        assert_eq!(s, "stale");
+    }
+
+    #[test]
+    fn layout_overflow_is_reported() {
+        assert_eq!(try_layout_offset(usize::MAX).err(), Some(Error::CapacityOverflow));
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(try_layout_offset(u32::MAX as usize + 1).err(), Some(Error::CapacityOverflow));
+        assert_eq!(try_layout_offset(16).unwrap().1, std::mem::size_of::<SymbolHdr>());
+    }
+
+    #[test]
+    fn shared_str_conversions() {
+        let _lock = test_lock();
+
+        let s1 = Symbol::from(Arc::<str>::from("example"));
+        let s2 = Symbol::from(Rc::<str>::from("example"));
+        assert_eq!(s1.0, s2.0);
+
+        assert_eq!(&*s1.to_arc_str(), "example");
+        assert_eq!(&*s1.to_rc_str(), "example");
+        assert_eq!(&*Arc::<str>::from(s1), "example");
+        assert_eq!(&*Rc::<str>::from(s2), "example");
+    }
+
     #[test]
     fn symbol_sizeof_is_equal_to_pointer() {
         // can be run in parallel
         assert_eq!(std::mem::size_of::<Symbol>(), std::mem::size_of::<*const ()>());
     }
 
+    #[test]
+    fn symbol_layout_is_equal_to_raw_symbol() {
+        // can be run in parallel
+        assert_eq!(std::mem::size_of::<Symbol>(), std::mem::size_of::<RawSymbol>());
+        assert_eq!(std::mem::align_of::<Symbol>(), std::mem::align_of::<RawSymbol>());
+        assert_eq!(std::mem::size_of::<Option<Symbol>>(), std::mem::size_of::<RawSymbol>());
+    }
+
+    #[test]
+    fn raw_symbol_round_trip() {
+        let _lock = test_lock();
+
+        let s1 = Symbol::from("example");
+        let raw = s1.clone().into_raw();
+        assert_eq!(s1.ref_count(), 2);
+
+        let s2 = unsafe { Symbol::from_raw(raw) };
+        assert_eq!(s1.0, s2.0);
+        assert_eq!(s2.ref_count(), 2);
+    }
+
     #[test]
     fn optional_symbol_sizeof_is_equal_to_pointer() {
         // can be run in parallel
         assert_eq!(std::mem::size_of::<Option<Symbol>>(), std::mem::size_of::<*const ()>());
     }
-}
-
 
+    #[test]
+    fn lowercase_symbol() {
+        let _lock = test_lock();
 
+        let s = Symbol::from("MiXeD");
+        assert_eq!(s.to_lowercase_symbol(), "mixed");
+        let l = Symbol::from("lower");
+        assert!(l.to_lowercase_symbol() == l);
+    }
+}
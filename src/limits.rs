@@ -0,0 +1,107 @@
+use super::Error;
+
+use parking_lot::RwLock;
+
+/// Limits checked before a new symbol is created, protecting against interning
+/// of untrusted input.
+///
+/// Strings which are already interned are not checked again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymbolLimits {
+    /// Maximal length of a symbol in bytes.
+    pub max_len: Option<usize>,
+    /// Rejects strings containing NUL characters.
+    pub reject_nul: bool,
+    /// Rejects strings containing control characters, including NUL.
+    pub reject_control: bool,
+}
+
+impl SymbolLimits {
+    /// Limits which accept any string.
+    pub const NONE: SymbolLimits = SymbolLimits {
+        max_len: None,
+        reject_nul: false,
+        reject_control: false,
+    };
+
+    pub(crate) fn check(&self, value: &str) -> Result<(), Error> {
+        if let Some(max) = self.max_len {
+            if value.len() > max {
+                return Err(Error::TooLong { len: value.len(), max });
+            }
+        }
+        if self.reject_control {
+            if let Some(c) = value.chars().find(|c| c.is_control()) {
+                return Err(Error::InvalidChar(c));
+            }
+        } else if self.reject_nul && value.contains('\0') {
+            return Err(Error::InvalidChar('\0'));
+        }
+        Ok(())
+    }
+}
+
+impl Default for SymbolLimits {
+    fn default() -> Self {
+        SymbolLimits::NONE
+    }
+}
+
+static LIMITS: RwLock<SymbolLimits> = parking_lot::const_rwlock(SymbolLimits::NONE);
+
+/// Sets limits for new symbols. Violations are reported as errors by [`Symbol::try_new`](crate::Symbol::try_new),
+/// infallible interning functions panic.
+pub fn set_limits(limits: SymbolLimits) {
+    *LIMITS.write() = limits;
+}
+
+pub fn limits() -> SymbolLimits {
+    *LIMITS.read()
+}
+
+#[inline]
+pub(crate) fn check_limits(value: &str) -> Result<(), Error> {
+    LIMITS.read().check(value)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn limits_reject_new_symbols() {
+        let _lock = test_lock();
+
+        let existing = Symbol::new("existing\n");
+        set_limits(SymbolLimits { max_len: Some(8), reject_nul: true, reject_control: false });
+        let res = (
+            Symbol::try_new("too long value"),
+            Symbol::try_new("nul\0"),
+            Symbol::try_new("tab\t"),
+            Symbol::try_new("existing\n"),
+        );
+        set_limits(SymbolLimits { reject_control: true, ..SymbolLimits::NONE });
+        let control = Symbol::try_new("other\ttab");
+        set_limits(SymbolLimits::default());
+
+        assert_eq!(res.0, Err(Error::TooLong { len: 14, max: 8 }));
+        assert_eq!(res.1, Err(Error::InvalidChar('\0')));
+        assert_eq!(res.2.unwrap(), "tab\t");
+        assert_eq!(res.3, Ok(existing));
+        assert_eq!(control, Err(Error::InvalidChar('\t')));
+        assert_eq!(limits(), SymbolLimits::NONE);
+    }
+
+    #[test]
+    fn deserialization_reports_limit_violations() {
+        let _lock = test_lock();
+
+        set_limits(SymbolLimits { max_len: Some(8), ..SymbolLimits::NONE });
+        let res = serde_json::from_str::<Symbol>("\"too long value\"");
+        set_limits(SymbolLimits::default());
+
+        assert!(res.unwrap_err().to_string().contains("exceeds the limit of 8 bytes"));
+    }
+}
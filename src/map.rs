@@ -1,17 +1,23 @@
-use super::Symbol;
+use super::{normalize, DefaultHashBuilder, Error, Symbol, SYMBOLS};
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::collections::hash_map::Entry;
 use std::borrow::Borrow;
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash};
 use heapsize::HeapSizeOf;
 use std::iter::FusedIterator;
 
 const SMALL_MAP_SIZE: usize = 8;
 
+/// Size below which the hashed index is dropped. It is lower than `SMALL_MAP_SIZE`,
+/// so that maps oscillating around the threshold do not repeatedly build and drop the index.
+const UNINDEXED_MAP_SIZE: usize = SMALL_MAP_SIZE / 2;
+
 pub struct SymbolMap<V> {
     items: Vec<(Symbol, V)>,
-    map: Option<Box<HashMap<Symbol, usize>>>
+    // boxed, so that small maps without index stay small
+    #[allow(clippy::box_collection)]
+    map: Option<Box<HashMap<Symbol, usize, DefaultHashBuilder>>>
 }
 
 impl<V> SymbolMap<V> {
@@ -26,13 +32,45 @@ impl<V> SymbolMap<V> {
         SymbolMap {
             items: Vec::with_capacity(capacity),
             map: if capacity > SMALL_MAP_SIZE {
-                Some(Box::new(HashMap::with_capacity(capacity)))
+                Some(Box::new(HashMap::with_capacity_and_hasher(capacity, Default::default())))
             } else {
                 None
             }
         }
     }
 
+    /// Same as [`SymbolMap::with_capacity`], but returns an error instead of panicking
+    /// when memory cannot be reserved.
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, Error> {
+        let mut m = SymbolMap::new();
+        m.items.try_reserve_exact(capacity)?;
+        if capacity > SMALL_MAP_SIZE {
+            let mut map = HashMap::with_hasher(Default::default());
+            map.try_reserve(capacity)?;
+            m.map = Some(Box::new(map));
+        }
+        Ok(m)
+    }
+
+    /// Creates map from key-value pairs, failing on the first duplicated key.
+    pub fn from_pairs_checked<I>(iter: I) -> Result<Self, DuplicateKeyError>
+        where I: IntoIterator<Item = (Symbol, V)>
+    {
+        let iter = iter.into_iter();
+        let mut map = SymbolMap::with_capacity(iter.size_hint().0);
+        for (index, (k, v)) in iter.enumerate() {
+            if let Some(first) = map.position(&k) {
+                return Err(DuplicateKeyError {
+                    key: k,
+                    first,
+                    duplicate: index,
+                });
+            }
+            map.insert(k, v);
+        }
+        Ok(map)
+    }
+
     pub fn capacity(&self) -> usize {
         self.items.capacity()
     }
@@ -51,6 +89,14 @@ impl<V> SymbolMap<V> {
         }
     }
 
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), Error> {
+        self.items.try_reserve(additional)?;
+        if let Some(m) = &mut self.map {
+            m.try_reserve(additional)?;
+        }
+        Ok(())
+    }
+
     pub fn len(&self) -> usize {
         self.items.len()
     }
@@ -58,6 +104,7 @@ impl<V> SymbolMap<V> {
     pub fn clear(&mut self) {
         self.items.clear();
         self.map = None;
+        self.debug_check_invariants();
     }
 
     pub fn contains_key<Q: ?Sized>(&self, k: &Q) -> bool
@@ -91,12 +138,58 @@ impl<V> SymbolMap<V> {
         }
     }
 
+    pub fn get_mut<Q: ?Sized>(&mut self, k: &Q) -> Option<&mut V>
+        where Q: AsRef<str> + Hash + Eq
+    {
+        if let Some(s) = Symbol::get(k) {
+            match self.map.as_ref() {
+                Some(m) => {
+                    match m.get(&s) {
+                        Some(&i) => unsafe { Some(&mut self.items.get_unchecked_mut(i).1) }
+                        None => None,
+                    }
+                },
+                None => self.items.iter_mut().find(|(k, _)| *k == s).map(|e| &mut e.1),
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Same as [`SymbolMap::get`], but skips lookup of the key in the global symbol table.
+    pub fn get_sym(&self, k: &Symbol) -> Option<&V> {
+        self.position(k).map(|i| unsafe { &self.items.get_unchecked(i).1 })
+    }
+
+    /// Same as [`SymbolMap::get_mut`], but skips lookup of the key in the global symbol table.
+    pub fn get_sym_mut(&mut self, k: &Symbol) -> Option<&mut V> {
+        self.position(k).map(move |i| unsafe { &mut self.items.get_unchecked_mut(i).1 })
+    }
+
+    /// Same as [`SymbolMap::contains_key`], but skips lookup of the key in the global symbol table.
+    pub fn contains_sym(&self, k: &Symbol) -> bool {
+        self.position(k).is_some()
+    }
+
+    /// Same as [`SymbolMap::remove`], but skips lookup of the key in the global symbol table.
+    pub fn remove_sym(&mut self, k: &Symbol) -> Option<V> {
+        self.position(k).map(|index| self.remove_index(index).1)
+    }
+
+    fn position(&self, k: &Symbol) -> Option<usize> {
+        match self.map.as_ref() {
+            Some(m) => m.get(k).cloned(),
+            None => self.items.iter().position(|(s, _)| s == k),
+        }
+    }
+
     fn rebuild_map(&mut self) {
-        if self.items.len() <= SMALL_MAP_SIZE {
+        let len = self.items.len();
+        if len <= UNINDEXED_MAP_SIZE || (self.map.is_none() && len <= SMALL_MAP_SIZE) {
             self.map = None;
         } else {
             if self.map.is_none() {
-                self.map = Some(Box::new(HashMap::with_capacity(self.items.capacity())));
+                self.map = Some(Box::new(HashMap::with_capacity_and_hasher(self.items.capacity(), Default::default())));
             }
             if let Some(m) = self.map.as_mut() {
                 m.clear();
@@ -105,41 +198,77 @@ impl<V> SymbolMap<V> {
                 }
             }
         }
+        self.debug_check_invariants();
+    }
+
+    /// Verifies consistency of the hashed index with the entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index does not map every key to its position, contains stale entries,
+    /// or if keys are duplicated.
+    pub fn check_invariants(&self) {
+        match self.map.as_ref() {
+            Some(m) => {
+                for (i, (k, _)) in self.items.iter().enumerate() {
+                    assert_eq!(m.get(k), Some(&i), "index entry for key {:?} does not match its position", k);
+                }
+                assert_eq!(m.len(), self.items.len(), "index contains stale entries");
+            }
+            None => {
+                assert!(self.items.len() <= SMALL_MAP_SIZE, "index missing for map with {} entries", self.items.len());
+                for (i, (k, _)) in self.items.iter().enumerate() {
+                    assert!(self.items[..i].iter().all(|(s, _)| s != k), "duplicated key {:?}", k);
+                }
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn debug_check_invariants(&self) {
+        #[cfg(all(debug_assertions, feature = "check-invariants"))]
+        self.check_invariants();
     }
 
     pub fn remove<Q: ?Sized>(&mut self, k: &Q) -> Option<V>
         where Q: AsRef<str> + Hash + Eq
     {
         if let Some(s) = Symbol::get(k) {
-            match self.map.as_mut() {
-                Some(m) => {
-                    match m.get(&s) {
-                        Some(&i) => {
-                            let e = self.items.remove(i);
-                            self.rebuild_map();
-                            Some(e.1)
-                        }
-                        None => None,
-                    }
-                },
-                None => {
-                    if let Some(index) = self.items.iter().position(|(k, _)| s == *k) {
-                        let e = self.items.remove(index);
-                        Some(e.1)
-                    } else {
-                        None
-                    }
-                },
-            }
+            self.position(&s).map(|index| self.remove_index(index).1)
         } else {
             None
         }
     }
 
     pub fn remove_at(&mut self, index: usize) -> Option<V> {
-        let old = self.items.remove(index);
-        self.rebuild_map();
-        Some(old.1)
+        Some(self.remove_index(index).1)
+    }
+
+    /// Same as [`SymbolMap::remove_at`], but returns an error instead of panicking
+    /// when `index` is out of bounds.
+    pub fn try_remove_at(&mut self, index: usize) -> Result<V, Error> {
+        let len = self.items.len();
+        if index >= len {
+            return Err(Error::IndexOutOfBounds { index, len });
+        }
+        Ok(self.remove_index(index).1)
+    }
+
+    /// Removes entry at `index`, updating only the indices of entries shifted by the removal.
+    fn remove_index(&mut self, index: usize) -> (Symbol, V) {
+        let e = self.items.remove(index);
+        if self.items.len() <= UNINDEXED_MAP_SIZE {
+            self.map = None;
+        } else if let Some(m) = self.map.as_mut() {
+            m.remove(&e.0);
+            for (k, _) in self.items[index..].iter() {
+                if let Some(i) = m.get_mut(k) {
+                    *i -= 1;
+                }
+            }
+        }
+        self.debug_check_invariants();
+        e
     }
 
     pub fn insert(&mut self, k: Symbol, mut v: V) -> Option<V> {
@@ -150,6 +279,7 @@ impl<V> SymbolMap<V> {
                         let index = self.items.len();
                         self.items.push((k, v));
                         ve.insert(index);
+                        self.debug_check_invariants();
                         None
                     }
                     Entry::Occupied(oe) => {
@@ -175,20 +305,78 @@ impl<V> SymbolMap<V> {
         }
     }
 
+    /// Inserts a new entry, failing if the key is already present in the map.
+    pub fn try_insert(&mut self, k: Symbol, v: V) -> Result<&mut V, OccupiedError<V>> {
+        if self.position(&k).is_some() {
+            return Err(OccupiedError {
+                key: k,
+                value: v,
+            });
+        }
+        self.insert(k, v);
+        Ok(&mut self.items.last_mut().unwrap().1)
+    }
+
     pub fn insert_at(&mut self, index: usize, k: Symbol, v: V) -> Option<V> {
         let old = self.remove(&k);
-        self.items.insert(index, (k, v));
-        self.rebuild_map();
+        self.insert_index(index, k, v);
         old
     }
 
-    pub fn pop_front(&mut self) -> Option<(Symbol, V)> {
-        match self.items.pop() {
-            Some(e) => {
-                self.rebuild_map();
-                Some(e)
+    /// Same as [`SymbolMap::insert_at`], but returns an error instead of panicking
+    /// when `index` is out of bounds. The map is left unchanged on error.
+    pub fn try_insert_at(&mut self, index: usize, k: Symbol, v: V) -> Result<Option<V>, Error> {
+        let len = self.items.len() - self.position(&k).is_some() as usize;
+        if index > len {
+            return Err(Error::IndexOutOfBounds { index, len });
+        }
+        Ok(self.insert_at(index, k, v))
+    }
+
+    /// Inserts entry with a key not present in the map at `index`,
+    /// updating only the indices of entries shifted by the insertion.
+    fn insert_index(&mut self, index: usize, k: Symbol, v: V) {
+        self.items.insert(index, (k.clone(), v));
+        match self.map.as_mut() {
+            Some(m) => {
+                for (k, _) in self.items[index + 1..].iter() {
+                    if let Some(i) = m.get_mut(k) {
+                        *i += 1;
+                    }
+                }
+                m.insert(k, index);
+                self.debug_check_invariants();
             }
-            None => None
+            None => self.rebuild_map(),
+        }
+    }
+
+    /// Renames key `old` to `new`, preserving entry position and value.
+    pub fn replace_key(&mut self, old: &Symbol, new: Symbol) -> Result<(), ReplaceError> {
+        let index = match self.position(old) {
+            Some(index) => index,
+            None => return Err(ReplaceError::NotFound(old.clone())),
+        };
+        if *old == new {
+            return Ok(());
+        }
+        if self.position(&new).is_some() {
+            return Err(ReplaceError::Occupied(new));
+        }
+        if let Some(m) = self.map.as_mut() {
+            m.remove(old);
+            m.insert(new.clone(), index);
+        }
+        self.items[index].0 = new;
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    pub fn pop_front(&mut self) -> Option<(Symbol, V)> {
+        if self.items.is_empty() {
+            None
+        } else {
+            Some(self.remove_index(self.items.len() - 1))
         }
     }
 
@@ -196,9 +384,7 @@ impl<V> SymbolMap<V> {
         if self.items.is_empty() {
             None
         } else {
-            let e = self.items.remove(self.items.len() - 1);
-            self.rebuild_map();
-            Some(e)
+            Some(self.remove_index(self.items.len() - 1))
         }
     }
 
@@ -221,6 +407,70 @@ impl<V> SymbolMap<V> {
     pub fn values_mut(&'_ mut self) -> ValuesMut<'_, V> {
         ValuesMut(self.items.iter_mut())
     }
+
+    /// Returns iterator over entries with keys starting with `prefix`, in insertion order.
+    pub fn range_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a Symbol, &'a V)> + 'a {
+        self.items.iter().filter(move |(k, _)| k.starts_with(prefix)).map(|(k, v)| (k, v))
+    }
+
+    /// Returns cursor pointing at entry at `index`. Index equal to map length points past the last entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn cursor_at(&mut self, index: usize) -> CursorMut<'_, V> {
+        assert!(index <= self.items.len(), "cursor index {} out of bounds", index);
+        CursorMut {
+            map: self,
+            index,
+        }
+    }
+
+    /// Same as [`SymbolMap::cursor_at`], but returns an error instead of panicking
+    /// when `index > len`.
+    pub fn try_cursor_at(&mut self, index: usize) -> Result<CursorMut<'_, V>, Error> {
+        let len = self.items.len();
+        if index > len {
+            return Err(Error::IndexOutOfBounds { index, len });
+        }
+        Ok(self.cursor_at(index))
+    }
+
+    /// Returns view of this map serialized with entries sorted by key,
+    /// producing output independent of insertion order.
+    pub fn sorted(&self) -> SortedSymbolMap<'_, V> {
+        SortedSymbolMap(self)
+    }
+}
+
+impl<V> SymbolMap<Vec<V>> {
+    pub fn push(&mut self, k: Symbol, v: V) {
+        match self.position(&k) {
+            Some(i) => self.items[i].1.push(v),
+            None => {
+                self.insert(k, vec![v]);
+            }
+        }
+    }
+}
+
+/// Groups values by their keys, preserving the order in which keys first appear.
+pub fn collect_grouped<I, V>(iter: I) -> SymbolMap<Vec<V>>
+    where I: IntoIterator<Item = (Symbol, V)>
+{
+    let iter = iter.into_iter();
+    let mut map = SymbolMap::with_capacity(iter.size_hint().0.min(SMALL_MAP_SIZE));
+    for (k, v) in iter {
+        map.push(k, v);
+    }
+    map
+}
+
+/// Groups values by keys computed with `key`, preserving the order in which keys first appear.
+pub fn group_by_key<I, V, F>(iter: I, mut key: F) -> SymbolMap<Vec<V>>
+    where I: IntoIterator<Item = V>, F: FnMut(&V) -> Symbol
+{
+    collect_grouped(iter.into_iter().map(|v| (key(&v), v)))
 }
 
 impl<V> Default for SymbolMap<V> {
@@ -229,12 +479,91 @@ impl<V> Default for SymbolMap<V> {
     }
 }
 
+impl<V> SymbolMap<V> {
+    /// Builds map from pairs with string keys, locking each table shard at most once.
+    ///
+    /// Distinct strings can still become the same key when a normalizer is installed
+    /// (see [`set_normalizer`](crate::set_normalizer)), in which case the last value wins.
+    pub(crate) fn from_string_pairs<I>(iter: I) -> Self
+        where I: IntoIterator<Item = (String, V)>
+    {
+        let (keys, values): (Vec<String>, Vec<V>) = iter.into_iter().unzip();
+        let keys: Vec<_> = keys.iter().map(|k| normalize(k)).collect();
+        let keys = SYMBOLS.intern_all(&keys);
+        let mut map = SymbolMap::with_capacity(keys.len());
+        for (k, v) in keys.into_iter().zip(values) {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+/// Keys which become equal after normalization keep the value coming last in iteration order of `map`.
+impl<V, S> From<HashMap<String, V, S>> for SymbolMap<V> {
+    fn from(map: HashMap<String, V, S>) -> Self {
+        SymbolMap::from_string_pairs(map)
+    }
+}
+
+/// Keys which become equal after normalization keep the value coming last in iteration order of `map`.
+impl<V> From<BTreeMap<String, V>> for SymbolMap<V> {
+    fn from(map: BTreeMap<String, V>) -> Self {
+        SymbolMap::from_string_pairs(map)
+    }
+}
+
+impl<V, S: BuildHasher + Default> From<SymbolMap<V>> for HashMap<String, V, S> {
+    fn from(map: SymbolMap<V>) -> Self {
+        map.items.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+    }
+}
+
+impl<V> From<SymbolMap<V>> for BTreeMap<String, V> {
+    fn from(map: SymbolMap<V>) -> Self {
+        map.items.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+    }
+}
+
+impl<V> IntoIterator for SymbolMap<V> {
+    type Item = (Symbol, V);
+    type IntoIter = IntoIter<V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.items.into_iter())
+    }
+}
+
+impl<'a, V> IntoIterator for &'a SymbolMap<V> {
+    type Item = (&'a Symbol, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V: Clone> Clone for SymbolMap<V> {
+    fn clone(&self) -> Self {
+        SymbolMap {
+            items: self.items.clone(),
+            map: self.map.clone(),
+        }
+    }
+}
+
 impl<V: std::fmt::Debug> std::fmt::Debug for SymbolMap<V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_map().entries(self.items.iter().map(|e| (&e.0, &e.1))).finish()
     }
 }
 
+/// Serializes entries in insertion order.
+impl<V: serde::Serialize> serde::Serialize for SymbolMap<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+        serializer.collect_map(self.items.iter().map(|e| (&e.0, &e.1)))
+    }
+}
+
 impl<V: HeapSizeOf> HeapSizeOf for SymbolMap<V> {
     fn heap_size_of_children(&self) -> usize {
         self.items.heap_size_of_children() + self.map.heap_size_of_children()
@@ -242,6 +571,111 @@ impl<V: HeapSizeOf> HeapSizeOf for SymbolMap<V> {
 }
 
 
+/// Serializable view of a [`SymbolMap`] emitting entries sorted by key, created by [`SymbolMap::sorted`].
+///
+/// Only entries of the wrapped map are sorted, nested maps are serialized as they are.
+#[derive(Debug)]
+pub struct SortedSymbolMap<'a, V>(&'a SymbolMap<V>);
+
+impl<'a, V: serde::Serialize> serde::Serialize for SortedSymbolMap<'a, V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+        let mut entries: Vec<&(Symbol, V)> = self.0.items.iter().collect();
+        entries.sort_unstable_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        serializer.collect_map(entries.into_iter().map(|e| (&e.0, &e.1)))
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKeyError {
+    key: Symbol,
+    first: usize,
+    duplicate: usize,
+}
+
+impl DuplicateKeyError {
+    pub fn key(&self) -> &Symbol {
+        &self.key
+    }
+
+    /// Position of the first occurrence of the key in the input.
+    pub fn first(&self) -> usize {
+        self.first
+    }
+
+    /// Position of the duplicated occurrence of the key in the input.
+    pub fn duplicate(&self) -> usize {
+        self.duplicate
+    }
+}
+
+impl std::fmt::Display for DuplicateKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "duplicate key {:?} at positions {} and {}", self.key, self.first, self.duplicate)
+    }
+}
+
+impl std::error::Error for DuplicateKeyError {}
+
+
+pub struct OccupiedError<V> {
+    key: Symbol,
+    value: V,
+}
+
+impl<V> OccupiedError<V> {
+    pub fn key(&self) -> &Symbol {
+        &self.key
+    }
+
+    /// Value which was not inserted.
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    pub fn into_parts(self) -> (Symbol, V) {
+        (self.key, self.value)
+    }
+}
+
+impl<V: std::fmt::Debug> std::fmt::Debug for OccupiedError<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OccupiedError")
+            .field("key", &self.key)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl<V> std::fmt::Display for OccupiedError<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "key {:?} is already present in the map", self.key)
+    }
+}
+
+impl<V: std::fmt::Debug> std::error::Error for OccupiedError<V> {}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplaceError {
+    /// Replaced key is not present in the map.
+    NotFound(Symbol),
+    /// New key is already present in the map.
+    Occupied(Symbol),
+}
+
+impl std::fmt::Display for ReplaceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplaceError::NotFound(k) => write!(f, "key {:?} is not present in the map", k),
+            ReplaceError::Occupied(k) => write!(f, "key {:?} is already present in the map", k),
+        }
+    }
+}
+
+impl std::error::Error for ReplaceError {}
+
+
 pub struct Iter<'a, V: 'a>(std::slice::Iter<'a, (Symbol, V)>);
 
 impl<'a, V: 'a> Iterator for Iter<'a, V> {
@@ -265,6 +699,29 @@ impl<'a, V: 'a> ExactSizeIterator for Iter<'a, V> {
 impl<'a, V: 'a> FusedIterator for Iter<'a, V> { }
 
 
+pub struct IntoIter<V>(std::vec::IntoIter<(Symbol, V)>);
+
+impl<V> Iterator for IntoIter<V> {
+    type Item = (Symbol, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<V> ExactSizeIterator for IntoIter<V> {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<V> FusedIterator for IntoIter<V> { }
+
+
 pub struct IterMut<'a, V: 'a>(std::slice::IterMut<'a, (Symbol, V)>);
 
 impl<'a, V: 'a> Iterator for IterMut<'a, V> {
@@ -357,10 +814,87 @@ impl<'a, V: 'a> ExactSizeIterator for ValuesMut<'a, V> {
 impl<'a, V: 'a> FusedIterator for ValuesMut<'a, V> { }
 
 
+/// Cursor over [`SymbolMap`] entries, allowing in-place edits while walking the map.
+///
+/// Cursor position is an index in range `0..=len`, where `len` points past the last entry.
+pub struct CursorMut<'a, V: 'a> {
+    map: &'a mut SymbolMap<V>,
+    index: usize,
+}
+
+impl<'a, V: 'a> CursorMut<'a, V> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns entry at the cursor position, or `None` if cursor points past the last entry.
+    pub fn current(&mut self) -> Option<(&Symbol, &mut V)> {
+        self.map.items.get_mut(self.index).map(|(k, v)| (&*k, v))
+    }
+
+    pub fn peek_next(&self) -> Option<(&Symbol, &V)> {
+        self.map.items.get(self.index + 1).map(|(k, v)| (k, v))
+    }
+
+    pub fn peek_prev(&self) -> Option<(&Symbol, &V)> {
+        if self.index == 0 {
+            None
+        } else {
+            self.map.items.get(self.index - 1).map(|(k, v)| (k, v))
+        }
+    }
+
+    /// Moves cursor to the next position, returning `false` if it already points past the last entry.
+    pub fn move_next(&mut self) -> bool {
+        if self.index < self.map.items.len() {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Moves cursor to the previous position, returning `false` if it already points at the first entry.
+    pub fn move_prev(&mut self) -> bool {
+        if self.index > 0 {
+            self.index -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Inserts entry at the cursor position, moving the current entry forward.
+    /// Cursor then points at the inserted entry.
+    pub fn insert(&mut self, k: Symbol, v: V) -> Result<(), OccupiedError<V>> {
+        if self.map.position(&k).is_some() {
+            return Err(OccupiedError {
+                key: k,
+                value: v,
+            });
+        }
+        self.map.insert_index(self.index, k, v);
+        Ok(())
+    }
+
+    /// Removes entry at the cursor position. Cursor then points at the following entry.
+    pub fn remove_current(&mut self) -> Option<(Symbol, V)> {
+        if self.index < self.map.items.len() {
+            Some(self.map.remove_index(self.index))
+        } else {
+            None
+        }
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
     use crate::*;
     use crate::tests::test_lock;
+    use super::{SMALL_MAP_SIZE, UNINDEXED_MAP_SIZE};
+
+    use std::collections::{BTreeMap, HashMap};
 
     #[test]
     fn small_map_smoke_test() {
@@ -375,6 +909,317 @@ mod tests {
         assert_eq!(m.len(), 2);
         assert_eq!(m.get("key1"), Some(&"v3"));
         assert_eq!(m.get("key4"), None);
-        assert_eq!(SYMBOLS.lock().len(), 3);
+        assert_eq!(SYMBOLS.len(), 3);
+    }
+
+    #[test]
+    fn get_mut_in_small_and_hashed_map() {
+        let _lock = test_lock();
+
+        let mut m = SymbolMap::new();
+        for i in 0..20 {
+            m.insert(Symbol::new(format!("key{}", i)), i);
+            *m.get_mut("key0").unwrap() += 1;
+        }
+
+        assert_eq!(m.get("key0"), Some(&20));
+        assert_eq!(m.get_mut("key21"), None);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn collect_grouped_preserves_key_order() {
+        let _lock = test_lock();
+
+        let pairs = vec![("b", 1), ("a", 2), ("b", 3), ("c", 4), ("a", 5)];
+        let m = collect_grouped(pairs.into_iter().map(|(k, v)| (Symbol::from(k), v)));
+
+        assert_eq!(m.keys().collect::<Vec<_>>(), ["b", "a", "c"].iter().collect::<Vec<_>>());
+        assert_eq!(m.get("a"), Some(&vec![2, 5]));
+        assert_eq!(m.get("b"), Some(&vec![1, 3]));
+        assert_eq!(m.get("c"), Some(&vec![4]));
+    }
+
+    #[test]
+    fn group_by_key_uses_key_function() {
+        let _lock = test_lock();
+
+        let m = group_by_key(vec!["apple", "avocado", "banana", "blueberry", "cherry"], |s| Symbol::new(&s[..1]));
+
+        assert_eq!(m.len(), 3);
+        assert_eq!(m.get("a"), Some(&vec!["apple", "avocado"]));
+        assert_eq!(m.get("b"), Some(&vec!["banana", "blueberry"]));
+        assert_eq!(m.get("c"), Some(&vec!["cherry"]));
+    }
+
+    #[test]
+    fn from_pairs_checked_reports_first_duplicate() {
+        let _lock = test_lock();
+
+        let ok = SymbolMap::from_pairs_checked(vec![("a".into(), 1), ("b".into(), 2)]).unwrap();
+        assert_eq!(ok.len(), 2);
+        assert_eq!(ok.get("b"), Some(&2));
+
+        let pairs: Vec<(Symbol, usize)> = (0..12).map(|i| (Symbol::new(format!("k{}", i % 10)), i)).collect();
+        let err = SymbolMap::from_pairs_checked(pairs).unwrap_err();
+        assert_eq!(err.key(), "k0");
+        assert_eq!(err.first(), 0);
+        assert_eq!(err.duplicate(), 10);
+        assert_eq!(err.to_string(), "duplicate key \"k0\" at positions 0 and 10");
+    }
+
+    #[test]
+    fn try_insert_rejects_existing_keys() {
+        let _lock = test_lock();
+
+        let mut m = SymbolMap::new();
+        *m.try_insert("a".into(), 1).unwrap() += 10;
+        assert_eq!(m.get("a"), Some(&11));
+
+        let err = m.try_insert("a".into(), 2).unwrap_err();
+        assert_eq!(err.key(), "a");
+        assert_eq!(err.value(), &2);
+        assert_eq!(err.to_string(), "key \"a\" is already present in the map");
+        assert_eq!(err.into_parts().1, 2);
+        assert_eq!(m.get("a"), Some(&11));
+        assert_eq!(m.len(), 1);
+    }
+
+    #[test]
+    fn replace_key_preserves_position() {
+        let _lock = test_lock();
+
+        for count in [3, 20].iter() {
+            let mut m = SymbolMap::new();
+            for i in 0..*count {
+                m.insert(Symbol::new(format!("k{}", i)), i);
+            }
+
+            m.replace_key(&"k1".into(), "renamed".into()).unwrap();
+
+            assert_eq!(m.keys().nth(1).unwrap(), "renamed");
+            assert_eq!(m.get("renamed"), Some(&1));
+            assert_eq!(m.get("k1"), None);
+            assert_eq!(m.len(), *count);
+
+            assert_eq!(m.replace_key(&"k1".into(), "x".into()), Err(ReplaceError::NotFound("k1".into())));
+            assert_eq!(m.replace_key(&"k0".into(), "k2".into()), Err(ReplaceError::Occupied("k2".into())));
+            assert_eq!(m.replace_key(&"k0".into(), "k0".into()), Ok(()));
+        }
+    }
+
+    #[test]
+    fn invariants_hold_after_mutations() {
+        let _lock = test_lock();
+
+        let mut m = SymbolMap::new();
+        for i in 0..20 {
+            m.insert(Symbol::new(format!("k{}", i)), i);
+            m.check_invariants();
+        }
+        m.remove("k3");
+        m.check_invariants();
+        m.insert_at(0, "k19".into(), 19);
+        m.check_invariants();
+        while m.len() > 2 {
+            m.remove_at(1);
+            m.check_invariants();
+        }
+        m.clear();
+        m.check_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match its position")]
+    fn check_invariants_detects_index_desync() {
+        let _lock = test_lock();
+
+        let mut m = SymbolMap::new();
+        for i in 0..10 {
+            m.insert(Symbol::new(format!("k{}", i)), i);
+        }
+        m.items.swap(0, 1);
+        m.check_invariants();
+    }
+
+    #[test]
+    fn index_is_kept_near_threshold() {
+        let _lock = test_lock();
+
+        let mut m = SymbolMap::new();
+        for i in 0..=SMALL_MAP_SIZE {
+            m.insert(Symbol::new(format!("k{}", i)), i);
+        }
+        assert!(m.map.is_some());
+
+        m.remove_at(0);
+        assert!(m.map.is_some());
+        m.insert("k0".into(), 0);
+        assert!(m.map.is_some());
+
+        while m.len() > UNINDEXED_MAP_SIZE {
+            m.remove_at(0);
+        }
+        assert!(m.map.is_none());
+        m.check_invariants();
+    }
+
+    #[test]
+    fn remove_in_hashed_mode_keeps_order() {
+        let _lock = test_lock();
+
+        let mut m = SymbolMap::new();
+        for i in 0..40 {
+            m.insert(Symbol::new(format!("k{}", i)), i);
+        }
+        for i in (0..40).step_by(3) {
+            assert_eq!(m.remove(format!("k{}", i).as_str()), Some(i));
+            m.check_invariants();
+        }
+        assert_eq!(m.remove("k0"), None);
+
+        let values: Vec<_> = m.values().cloned().collect();
+        assert_eq!(values, (0..40).filter(|i| i % 3 != 0).collect::<Vec<_>>());
+        for v in values {
+            assert_eq!(m.get(format!("k{}", v).as_str()), Some(&v));
+        }
+    }
+
+    #[test]
+    fn conversions_with_std_maps() {
+        let _lock = test_lock();
+
+        let btree: BTreeMap<String, usize> = (0..20).map(|i| (format!("k{:02}", i), i)).collect();
+        let m = SymbolMap::from(btree.clone());
+        assert_eq!(m.len(), 20);
+        assert_eq!(m.keys().next().unwrap(), "k00");
+        assert_eq!(m.get("k13"), Some(&13));
+        assert_eq!(BTreeMap::from(m), btree);
+
+        let hash: HashMap<String, usize> = btree.into_iter().take(3).collect();
+        let m = SymbolMap::from(hash.clone());
+        assert_eq!(m.get("k02"), Some(&2));
+        assert_eq!(HashMap::<String, usize>::from(m), hash);
+    }
+
+    #[test]
+    fn cursor_walks_and_edits() {
+        let _lock = test_lock();
+
+        for count in [4, 20].iter() {
+            let mut m = SymbolMap::new();
+            for i in 0..*count {
+                m.insert(Symbol::new(format!("k{}", i)), i);
+            }
+
+            let mut c = m.cursor_at(0);
+            assert_eq!(c.peek_prev(), None);
+            while let Some((k, v)) = c.current() {
+                if *v % 2 == 1 {
+                    assert!(k.starts_with('k'));
+                    c.remove_current();
+                } else {
+                    *v *= 10;
+                    c.move_next();
+                }
+            }
+            assert!(!c.move_next());
+            assert!(c.move_prev());
+            assert_eq!(c.peek_next(), None);
+            c.insert("x".into(), 1).unwrap();
+            assert!(c.insert("k0".into(), 2).is_err());
+            assert_eq!(c.current().map(|(k, _)| k.to_string()), Some("x".to_string()));
+
+            m.check_invariants();
+            let mut expected: Vec<_> = (0..*count).filter(|i| i % 2 == 0).map(|i| i * 10).collect();
+            expected.insert(expected.len() - 1, 1);
+            assert_eq!(m.values().cloned().collect::<Vec<_>>(), expected);
+            assert_eq!(m.get("x"), Some(&1));
+        }
+    }
+
+    #[test]
+    fn try_variants_report_out_of_bounds() {
+        let _lock = test_lock();
+
+        let mut m = SymbolMap::try_with_capacity(2 * SMALL_MAP_SIZE).unwrap();
+        m.insert(Symbol::from("a"), 1);
+        m.insert(Symbol::from("b"), 2);
+
+        assert_eq!(m.try_remove_at(2), Err(Error::IndexOutOfBounds { index: 2, len: 2 }));
+        assert_eq!(m.try_insert_at(2, Symbol::from("a"), 3), Err(Error::IndexOutOfBounds { index: 2, len: 1 }));
+        assert_eq!(m.get("a"), Some(&1));
+        assert!(m.try_cursor_at(3).is_err());
+        assert!(m.try_reserve(usize::MAX).is_err());
+
+        assert_eq!(m.try_insert_at(1, Symbol::from("a"), 3), Ok(Some(1)));
+        assert_eq!(m.try_remove_at(0), Ok(2));
+        assert_eq!(m.len(), 1);
+    }
+
+    #[test]
+    fn sorted_serialization_ignores_insertion_order() {
+        let _lock = test_lock();
+
+        let mut m1 = SymbolMap::new();
+        let mut m2 = SymbolMap::new();
+        for (i, k) in ["b", "c", "a"].iter().enumerate() {
+            m1.insert(Symbol::from(*k), i);
+        }
+        for (k, v) in [("c", 1), ("a", 2), ("b", 0)].iter() {
+            m2.insert(Symbol::from(*k), *v);
+        }
+
+        assert_eq!(serde_json::to_string(&m1).unwrap(), r#"{"b":0,"c":1,"a":2}"#);
+        assert_eq!(serde_json::to_string(&m1.sorted()).unwrap(), r#"{"a":2,"b":0,"c":1}"#);
+        assert_eq!(serde_json::to_string(&m2.sorted()).unwrap(), serde_json::to_string(&m1.sorted()).unwrap());
+    }
+
+    #[test]
+    fn symbol_keyed_access() {
+        let _lock = test_lock();
+
+        let keys: Vec<Symbol> = (0..2 * SMALL_MAP_SIZE).map(|i| Symbol::from(format!("k{}", i))).collect();
+        for len in [2, 2 * SMALL_MAP_SIZE].iter() {
+            let mut m = SymbolMap::new();
+            for (i, k) in keys[..*len].iter().enumerate() {
+                m.insert(k.clone(), i);
+            }
+            let last = &keys[*len - 1];
+            assert_eq!(m.get_sym(last), Some(&(*len - 1)));
+            *m.get_sym_mut(last).unwrap() += 1;
+            assert!(m.contains_sym(last));
+            assert_eq!(m.remove_sym(last), Some(*len));
+            assert!(!m.contains_sym(last));
+            assert_eq!(m.get_sym(&Symbol::from("missing")), None);
+        }
+    }
+
+    #[test]
+    fn keys_colliding_after_normalization_keep_last_value() {
+        let _lock = test_lock();
+
+        let btree: BTreeMap<String, usize> = vec![("A".to_string(), 0), ("a".to_string(), 1)].into_iter().collect();
+        set_normalizer(Lowercase);
+        let m = SymbolMap::from(btree);
+        clear_normalizer();
+
+        assert_eq!(m.len(), 1);
+        assert_eq!(m.get("a"), Some(&1));
+    }
+
+    #[test]
+    fn range_prefix_filters_keys() {
+        let _lock = test_lock();
+
+        let mut m = SymbolMap::new();
+        m.insert("http.port".into(), 1);
+        m.insert("db.host".into(), 2);
+        m.insert("http.host".into(), 3);
+        m.insert("https".into(), 4);
+
+        let entries: Vec<_> = m.range_prefix("http.").map(|(k, &v)| (k.to_string(), v)).collect();
+        assert_eq!(entries, [("http.port".to_string(), 1), ("http.host".to_string(), 3)]);
+        assert_eq!(m.range_prefix("").count(), 4);
+        assert_eq!(m.range_prefix("ftp.").count(), 0);
+    }
+}
@@ -0,0 +1,147 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "global")]
+use parking_lot::RwLock;
+#[cfg(feature = "global")]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Canonicalizes strings before they are looked up in or inserted into the symbol table.
+///
+/// Normalizers are expected to be idempotent, i.e. normalizing an already normalized string
+/// should return it unchanged. Unicode normalization forms (e.g. NFC) can be provided
+/// by implementing this trait over an external normalization library.
+pub trait Normalizer: Send + Sync {
+    fn normalize<'a>(&self, value: &'a str) -> Cow<'a, str>;
+}
+
+impl<F> Normalizer for F
+    where F: for<'a> Fn(&'a str) -> Cow<'a, str> + Send + Sync
+{
+    fn normalize<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        self(value)
+    }
+}
+
+/// Applies both normalizers in sequence.
+impl<A: Normalizer, B: Normalizer> Normalizer for (A, B) {
+    fn normalize<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        match self.0.normalize(value) {
+            Cow::Borrowed(s) => self.1.normalize(s),
+            Cow::Owned(s) => Cow::Owned(self.1.normalize(&s).into_owned()),
+        }
+    }
+}
+
+/// Removes leading and trailing whitespace.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Trim;
+
+impl Normalizer for Trim {
+    fn normalize<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        Cow::Borrowed(value.trim())
+    }
+}
+
+/// Converts strings to lowercase, as defined by [`str::to_lowercase`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lowercase;
+
+impl Normalizer for Lowercase {
+    fn normalize<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        if value.chars().any(char::is_uppercase) {
+            Cow::Owned(value.to_lowercase())
+        } else {
+            Cow::Borrowed(value)
+        }
+    }
+}
+
+#[cfg(feature = "global")]
+lazy_static! {
+    static ref NORMALIZER: RwLock<Option<Box<dyn Normalizer>>> = RwLock::new(None);
+}
+
+/// Set while a normalizer is installed, so that lookups without one do not take the lock.
+#[cfg(feature = "global")]
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Installs normalizer applied to all strings passed to [`Symbol::new`](crate::Symbol::new),
+/// [`Symbol::get`](crate::Symbol::get) and other interning functions, replacing the previous one.
+///
+/// Symbols interned before the normalizer was installed are not affected.
+#[cfg(feature = "global")]
+pub fn set_normalizer<N: Normalizer + 'static>(normalizer: N) {
+    let mut n = NORMALIZER.write();
+    *n = Some(Box::new(normalizer));
+    INSTALLED.store(true, Ordering::Release);
+}
+
+/// Removes the installed normalizer, if any.
+#[cfg(feature = "global")]
+pub fn clear_normalizer() {
+    let mut n = NORMALIZER.write();
+    *n = None;
+    INSTALLED.store(false, Ordering::Release);
+}
+
+#[cfg(feature = "global")]
+#[inline]
+pub(crate) fn normalize(value: &str) -> Cow<'_, str> {
+    if !INSTALLED.load(Ordering::Acquire) {
+        return Cow::Borrowed(value);
+    }
+    match &*NORMALIZER.read() {
+        Some(n) => n.normalize(value),
+        None => Cow::Borrowed(value),
+    }
+}
+
+
+#[cfg(all(test, feature = "global"))]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    use std::borrow::Cow;
+
+    #[test]
+    fn builtin_normalizers() {
+        assert_eq!(Trim.normalize("  a b "), "a b");
+        assert_eq!(Lowercase.normalize("ABc"), "abc");
+        assert!(matches!(Lowercase.normalize("abc"), Cow::Borrowed("abc")));
+        assert_eq!((Trim, Lowercase).normalize(" Ab\t"), "ab");
+    }
+
+    #[test]
+    fn installed_normalizer_applies_to_lookup_and_interning() {
+        let _lock = test_lock();
+
+        set_normalizer((Trim, Lowercase));
+        let s1 = Symbol::new(" Hello ");
+        let s2 = Symbol::try_new("HELLO").unwrap();
+        let s3 = Symbol::get("hello\n");
+        let s4 = Symbol::from(" x ".to_string());
+        clear_normalizer();
+
+        assert_eq!(s1, "hello");
+        assert_eq!(s1, s2);
+        assert_eq!(s3.as_ref(), Some(&s1));
+        assert_eq!(s4, "x");
+        assert_eq!(Symbol::new("Hello"), "Hello");
+    }
+
+    fn underscores(s: &str) -> Cow<'_, str> {
+        Cow::Owned(s.replace('-', "_"))
+    }
+
+    #[test]
+    fn function_normalizer() {
+        let _lock = test_lock();
+
+        set_normalizer(underscores);
+        let s = Symbol::new("foo-bar");
+        clear_normalizer();
+
+        assert_eq!(s, "foo_bar");
+    }
+}
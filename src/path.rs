@@ -0,0 +1,185 @@
+use super::Symbol;
+
+use std::iter::FromIterator;
+use std::str::FromStr;
+
+/// Hierarchical key made of symbol segments, with textual form `a.b.c`.
+///
+/// Paths are ordered segment by segment, so parents sort directly before their children.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SymbolPath(Vec<Symbol>);
+
+impl SymbolPath {
+    pub fn new() -> SymbolPath {
+        SymbolPath(Vec::new())
+    }
+
+    /// Parses dotted path. Empty string is parsed as an empty path.
+    pub fn parse(value: &str) -> SymbolPath {
+        if value.is_empty() {
+            SymbolPath::new()
+        } else {
+            value.split('.').map(Symbol::from).collect()
+        }
+    }
+
+    pub fn segments(&self) -> &[Symbol] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn first(&self) -> Option<&Symbol> {
+        self.0.first()
+    }
+
+    pub fn last(&self) -> Option<&Symbol> {
+        self.0.last()
+    }
+
+    /// Returns path without the last segment, or `None` for an empty path.
+    pub fn parent(&self) -> Option<SymbolPath> {
+        let (_, parent) = self.0.split_last()?;
+        Some(SymbolPath(parent.to_vec()))
+    }
+
+    /// Returns path extended with `segment`.
+    pub fn child<S: Into<Symbol>>(&self, segment: S) -> SymbolPath {
+        let mut path = self.clone();
+        path.push(segment);
+        path
+    }
+
+    /// Returns concatenation of this path and `other`.
+    pub fn join(&self, other: &SymbolPath) -> SymbolPath {
+        self.0.iter().chain(other.0.iter()).cloned().collect()
+    }
+
+    pub fn push<S: Into<Symbol>>(&mut self, segment: S) {
+        self.0.push(segment.into());
+    }
+
+    pub fn pop(&mut self) -> Option<Symbol> {
+        self.0.pop()
+    }
+
+    pub fn starts_with(&self, prefix: &SymbolPath) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+
+    /// Returns path relative to `prefix`, if this path starts with it.
+    pub fn strip_prefix(&self, prefix: &SymbolPath) -> Option<SymbolPath> {
+        self.0.strip_prefix(&prefix.0[..]).map(|rest| SymbolPath(rest.to_vec()))
+    }
+
+    /// Interns the textual form of the path.
+    pub fn to_symbol(&self) -> Symbol {
+        super::join(&self.0, ".")
+    }
+}
+
+impl FromStr for SymbolPath {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(SymbolPath::parse(s))
+    }
+}
+
+impl<'a> From<&'a str> for SymbolPath {
+    fn from(value: &'a str) -> Self {
+        SymbolPath::parse(value)
+    }
+}
+
+impl From<Vec<Symbol>> for SymbolPath {
+    fn from(segments: Vec<Symbol>) -> Self {
+        SymbolPath(segments)
+    }
+}
+
+impl FromIterator<Symbol> for SymbolPath {
+    fn from_iter<I: IntoIterator<Item = Symbol>>(iter: I) -> Self {
+        SymbolPath(iter.into_iter().collect())
+    }
+}
+
+impl<'a> IntoIterator for &'a SymbolPath {
+    type Item = &'a Symbol;
+    type IntoIter = std::slice::Iter<'a, Symbol>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl std::fmt::Display for SymbolPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, s) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            f.write_str(s)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for SymbolPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.to_string())
+    }
+}
+
+impl serde::Serialize for SymbolPath {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for SymbolPath {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: serde::Deserializer<'de> {
+        Ok(SymbolPath::parse(&String::deserialize(deserializer)?))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn parse_and_navigate() {
+        let _lock = test_lock();
+
+        let p = SymbolPath::parse("server.http.port");
+        assert_eq!(p.len(), 3);
+        assert_eq!(p.to_string(), "server.http.port");
+        assert_eq!(p.last(), Some(&Symbol::new("port")));
+        assert_eq!(p.parent(), Some(SymbolPath::from("server.http")));
+        assert_eq!(SymbolPath::from("server.http").child("port"), p);
+        assert_eq!(SymbolPath::from("server").join(&"http.port".parse().unwrap()), p);
+        assert!(p.starts_with(&SymbolPath::from("server")));
+        assert_eq!(p.strip_prefix(&SymbolPath::from("server")), Some(SymbolPath::from("http.port")));
+        assert_eq!(p.to_symbol(), "server.http.port");
+        assert_eq!(SymbolPath::parse(""), SymbolPath::new());
+        assert_eq!(SymbolPath::new().parent(), None);
+    }
+
+    #[test]
+    fn parents_sort_before_children() {
+        let _lock = test_lock();
+
+        let mut paths: Vec<SymbolPath> = ["a.b", "a", "a-b", "a.b.c", "b"].iter().map(|&p| p.into()).collect();
+        paths.sort();
+        let sorted: Vec<String> = paths.iter().map(|p| p.to_string()).collect();
+        assert_eq!(sorted, ["a", "a.b", "a.b.c", "a-b", "b"]);
+    }
+}
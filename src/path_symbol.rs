@@ -0,0 +1,142 @@
+use super::ByteSymbol;
+
+use std::ffi::{OsStr, OsString};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// Interned path or other OS string, including contents which are not valid UTF-8.
+///
+/// Paths are stored in the platform encoding of `OsStr` in the [`ByteSymbol`] table, and are
+/// compared by pointer, so equality (as well as ordering and hashing) is on the exact encoded
+/// bytes, not on path components like for [`Path`].
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PathSymbol(ByteSymbol);
+
+impl PathSymbol {
+    pub fn new<P: AsRef<OsStr>>(value: P) -> PathSymbol {
+        PathSymbol(ByteSymbol::new(value.as_ref().as_encoded_bytes()))
+    }
+
+    /// Returns path symbol for `value`, if it is already interned.
+    pub fn get<P: AsRef<OsStr>>(value: P) -> Option<PathSymbol> {
+        ByteSymbol::get(value.as_ref().as_encoded_bytes()).map(PathSymbol)
+    }
+
+    #[inline]
+    pub fn as_os_str(&self) -> &OsStr {
+        // bytes are equal to the encoded bytes of an `OsStr` this symbol was created from
+        unsafe { OsStr::from_encoded_bytes_unchecked(self.0.as_bytes()) }
+    }
+
+    #[inline]
+    pub fn as_path(&self) -> &Path {
+        Path::new(self.as_os_str())
+    }
+
+    /// Returns the path as `str`, if it is valid unicode.
+    pub fn to_str(&self) -> Option<&str> {
+        self.as_os_str().to_str()
+    }
+}
+
+impl Deref for PathSymbol {
+    type Target = Path;
+
+    #[inline]
+    fn deref(&self) -> &Path {
+        self.as_path()
+    }
+}
+
+impl AsRef<Path> for PathSymbol {
+    #[inline]
+    fn as_ref(&self) -> &Path {
+        self.as_path()
+    }
+}
+
+impl AsRef<OsStr> for PathSymbol {
+    #[inline]
+    fn as_ref(&self) -> &OsStr {
+        self.as_os_str()
+    }
+}
+
+impl<'a> From<&'a Path> for PathSymbol {
+    fn from(value: &'a Path) -> Self {
+        PathSymbol::new(value)
+    }
+}
+
+impl From<PathBuf> for PathSymbol {
+    fn from(value: PathBuf) -> Self {
+        PathSymbol::new(value)
+    }
+}
+
+impl<'a> From<&'a OsStr> for PathSymbol {
+    fn from(value: &'a OsStr) -> Self {
+        PathSymbol::new(value)
+    }
+}
+
+impl From<OsString> for PathSymbol {
+    fn from(value: OsString) -> Self {
+        PathSymbol::new(value)
+    }
+}
+
+impl<'a> From<&'a str> for PathSymbol {
+    fn from(value: &'a str) -> Self {
+        PathSymbol::new(value)
+    }
+}
+
+impl PartialEq<Path> for PathSymbol {
+    fn eq(&self, other: &Path) -> bool {
+        self.as_os_str() == other.as_os_str()
+    }
+}
+
+impl<'a> PartialEq<&'a Path> for PathSymbol {
+    fn eq(&self, other: &&'a Path) -> bool {
+        self.as_os_str() == other.as_os_str()
+    }
+}
+
+impl std::fmt::Debug for PathSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self.as_path(), f)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    use std::path::Path;
+
+    #[test]
+    fn paths_are_interned() {
+        let p = PathSymbol::new("/usr/lib/libc.so");
+        assert_eq!(PathSymbol::from(Path::new("/usr/lib/libc.so")), p);
+        assert_eq!(p, Path::new("/usr/lib/libc.so"));
+        assert_eq!(p.file_name().and_then(|n| n.to_str()), Some("libc.so"));
+        assert_eq!(p.to_str(), Some("/usr/lib/libc.so"));
+        assert_eq!(PathSymbol::get(p.parent().unwrap()), None);
+        assert_eq!(format!("{:?}", p), "\"/usr/lib/libc.so\"");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_paths_are_preserved() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let raw = std::ffi::OsStr::from_bytes(b"dir/\xff\xfe.txt");
+        let p = PathSymbol::new(raw);
+        assert_eq!(p.as_os_str(), raw);
+        assert_eq!(p.to_str(), None);
+        assert_eq!(PathSymbol::get(raw), Some(p));
+    }
+}
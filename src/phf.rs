@@ -0,0 +1,372 @@
+use super::{Symbol, SymbolHasher};
+
+use std::hash::Hasher;
+use std::sync::OnceLock;
+
+/// Average number of keys per displacement bucket.
+const BUCKET_SIZE: usize = 4;
+
+/// Number of `d1` displacements tried for every bucket, before moving to the next seed.
+const MAX_D1: usize = 64;
+
+struct Hashes {
+    g: usize,
+    f1: usize,
+    f2: usize,
+}
+
+#[inline]
+fn mix(mut x: u64) -> u64 {
+    // splitmix64 finalizer
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[inline]
+fn hashes(seed: u64, value: &str) -> Hashes {
+    let mut hasher = SymbolHasher::default();
+    hasher.write_u64(seed);
+    hasher.write(value.as_bytes());
+    let a = mix(hasher.finish());
+    let b = mix(a);
+    Hashes {
+        g: (a >> 32) as usize,
+        f1: a as u32 as usize,
+        f2: b as u32 as usize,
+    }
+}
+
+#[inline]
+fn displace(f1: usize, f2: usize, d1: usize, d2: usize, len: usize) -> usize {
+    (f1.wrapping_add(d1.wrapping_mul(f2)).wrapping_add(d2)) % len
+}
+
+
+/// Builder of [`PerfectHashSymbolSet`].
+#[derive(Debug, Default, Clone)]
+pub struct PerfectHashSymbolSetBuilder {
+    symbols: Vec<Symbol>,
+}
+
+impl PerfectHashSymbolSetBuilder {
+    pub fn new() -> Self {
+        PerfectHashSymbolSetBuilder {
+            symbols: Vec::new(),
+        }
+    }
+
+    pub fn insert(&mut self, s: Symbol) -> &mut Self {
+        if !self.symbols.contains(&s) {
+            self.symbols.push(s);
+        }
+        self
+    }
+
+    pub fn build(&self) -> PerfectHashSymbolSet {
+        let keys: Vec<&str> = self.symbols.iter().map(|s| s.as_str()).collect();
+        let (seed, displacements, slots) = build_table(&keys);
+        PerfectHashSymbolSet {
+            seed,
+            displacements,
+            slots: slots.into_iter().map(|k| self.symbols[k].clone()).collect(),
+        }
+    }
+}
+
+/// Perfect hash table: seed, bucket displacements and indices of keys stored in consecutive slots.
+pub(crate) type Table = (u64, Vec<(u32, u32)>, Vec<usize>);
+
+/// Computes perfect hash table for distinct `keys`.
+///
+/// Construction with a given seed fails only if some bucket cannot be placed within a bounded
+/// number of probes, so subsequent seeds are tried until it succeeds.
+pub(crate) fn build_table(keys: &[&str]) -> Table {
+    if keys.is_empty() {
+        return (0, Vec::new(), Vec::new());
+    }
+    let mut seed = 0;
+    loop {
+        if let Some(table) = try_build_table(keys, seed) {
+            return table;
+        }
+        seed += 1;
+    }
+}
+
+/// Set of free slots, with constant time removal.
+struct FreeSlots {
+    slots: Vec<usize>,
+    positions: Vec<usize>,
+}
+
+impl FreeSlots {
+    fn new(len: usize) -> FreeSlots {
+        FreeSlots {
+            slots: (0..len).collect(),
+            positions: (0..len).collect(),
+        }
+    }
+
+    fn remove(&mut self, slot: usize) {
+        let pos = self.positions[slot];
+        let last = self.slots.pop().unwrap();
+        if pos < self.slots.len() {
+            self.slots[pos] = last;
+            self.positions[last] = pos;
+        }
+    }
+}
+
+fn try_build_table(keys: &[&str], seed: u64) -> Option<Table> {
+    let len = keys.len();
+    let bucket_count = len.div_ceil(BUCKET_SIZE);
+    let hashes: Vec<Hashes> = keys.iter().map(|k| hashes(seed, k)).collect();
+
+    let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); bucket_count];
+    for (i, h) in hashes.iter().enumerate() {
+        buckets[h.g % bucket_count].push(i);
+    }
+    let mut order: Vec<usize> = (0..bucket_count).collect();
+    order.sort_unstable_by_key(|&b| std::cmp::Reverse(buckets[b].len()));
+
+    let mut displacements = vec![(0u32, 0u32); bucket_count];
+    let mut slots: Vec<Option<usize>> = vec![None; len];
+    let mut free = FreeSlots::new(len);
+    let mut taken = Vec::with_capacity(BUCKET_SIZE);
+
+    for b in order {
+        let keys = &buckets[b];
+        let first = match keys.first() {
+            Some(&k) => &hashes[k],
+            None => break,
+        };
+        // instead of searching all `d2` displacements, `d2` is chosen so that the first key
+        // of the bucket lands in one of the free slots; single keys are placed at once
+        let mut placed = false;
+        'search: for d1 in 0..MAX_D1 {
+            let base = displace(first.f1, first.f2, d1, 0, len);
+            'next: for &slot in free.slots.iter() {
+                let d2 = (slot + len - base) % len;
+                taken.clear();
+                for &k in keys.iter() {
+                    let idx = displace(hashes[k].f1, hashes[k].f2, d1, d2, len);
+                    if slots[idx].is_some() || taken.contains(&idx) {
+                        continue 'next;
+                    }
+                    taken.push(idx);
+                }
+                displacements[b] = (d1 as u32, d2 as u32);
+                placed = true;
+                break 'search;
+            }
+        }
+        if !placed {
+            return None;
+        }
+        for (&k, &idx) in keys.iter().zip(taken.iter()) {
+            slots[idx] = Some(k);
+            free.remove(idx);
+        }
+    }
+
+    Some((seed, displacements, slots.into_iter().map(Option::unwrap).collect()))
+}
+
+impl Extend<Symbol> for PerfectHashSymbolSetBuilder {
+    fn extend<I: IntoIterator<Item = Symbol>>(&mut self, iter: I) {
+        for s in iter {
+            self.insert(s);
+        }
+    }
+}
+
+
+/// Immutable set of symbols with minimal perfect hashing.
+///
+/// Every member is stored in its own slot, so membership test requires computing a single hash
+/// and comparing with a single stored symbol. It is intended for small, fixed sets
+/// looked up very frequently, like language keywords.
+pub struct PerfectHashSymbolSet {
+    seed: u64,
+    displacements: Vec<(u32, u32)>,
+    slots: Vec<Symbol>,
+}
+
+impl PerfectHashSymbolSet {
+    pub fn builder() -> PerfectHashSymbolSetBuilder {
+        PerfectHashSymbolSetBuilder::new()
+    }
+
+    /// Creates set from a table computed ahead of time, usually by code generated
+    /// with [`generate_static_symbols`](crate::generate_static_symbols).
+    /// `words` must be listed in slot order.
+    pub fn from_parts(seed: u64, displacements: &[(u32, u32)], words: &[&str]) -> PerfectHashSymbolSet {
+        let set = PerfectHashSymbolSet {
+            seed,
+            displacements: displacements.to_vec(),
+            slots: Symbol::new_all(words),
+        };
+        debug_assert!(words.iter().enumerate().all(|(i, w)| set.slot(w) == Some(i)), "invalid perfect hash table");
+        set
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    #[inline]
+    fn slot(&self, value: &str) -> Option<usize> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let h = hashes(self.seed, value);
+        let (d1, d2) = self.displacements[h.g % self.displacements.len()];
+        Some(displace(h.f1, h.f2, d1 as usize, d2 as usize, self.slots.len()))
+    }
+
+    /// Returns index of the slot holding `value`, which is unique within the set and lower than its length.
+    #[inline]
+    pub fn index_of(&self, value: &str) -> Option<usize> {
+        self.slot(value).filter(|&i| self.slots[i] == *value)
+    }
+
+    #[inline]
+    pub fn contains(&self, value: &str) -> bool {
+        self.index_of(value).is_some()
+    }
+
+    /// Tests membership of a symbol, comparing pointers instead of strings.
+    #[inline]
+    pub fn contains_symbol(&self, s: &Symbol) -> bool {
+        self.slot(s).is_some_and(|i| self.slots[i] == *s)
+    }
+
+    /// Returns symbol stored at slot `index`.
+    pub fn get(&self, index: usize) -> Option<&Symbol> {
+        self.slots.get(index)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Symbol> {
+        self.slots.iter()
+    }
+}
+
+static STATIC_SYMBOLS: OnceLock<PerfectHashSymbolSet> = OnceLock::new();
+
+/// Installs set of static symbols, checked by [`Symbol::new`] and [`Symbol::get`] before
+/// the global symbol table, so interning these strings never takes a lock.
+///
+/// All symbols in the set become permanent. Static symbols can be installed only once,
+/// returns `false` if they have already been installed.
+pub fn set_static_symbols(set: PerfectHashSymbolSet) -> bool {
+    set.iter().for_each(Symbol::pin);
+    STATIC_SYMBOLS.set(set).is_ok()
+}
+
+#[inline]
+pub(crate) fn lookup_static_symbol(value: &str) -> Option<Symbol> {
+    let set = STATIC_SYMBOLS.get()?;
+    set.index_of(value).map(|i| set.slots[i].clone())
+}
+
+impl std::iter::FromIterator<Symbol> for PerfectHashSymbolSet {
+    fn from_iter<I: IntoIterator<Item = Symbol>>(iter: I) -> Self {
+        let mut builder = PerfectHashSymbolSetBuilder::new();
+        builder.extend(iter);
+        builder.build()
+    }
+}
+
+impl std::fmt::Debug for PerfectHashSymbolSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.slots.iter()).finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    const KEYWORDS: &[&str] = &[
+        "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+        "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+        "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+        "where", "while", "async", "await", "dyn",
+    ];
+
+    #[test]
+    fn membership() {
+        let _lock = test_lock();
+
+        let set: PerfectHashSymbolSet = KEYWORDS.iter().map(|&k| Symbol::from(k)).collect();
+
+        assert_eq!(set.len(), KEYWORDS.len());
+        let mut indices: Vec<usize> = KEYWORDS.iter().map(|k| set.index_of(k).unwrap()).collect();
+        indices.sort_unstable();
+        assert_eq!(indices, (0..KEYWORDS.len()).collect::<Vec<_>>());
+
+        for k in KEYWORDS.iter() {
+            assert!(set.contains(k));
+            assert!(set.contains_symbol(&Symbol::from(*k)));
+            assert_eq!(set.get(set.index_of(k).unwrap()).unwrap(), k);
+        }
+        for k in ["", "As", "function", "selff", "x"].iter() {
+            assert!(!set.contains(k));
+            assert!(!set.contains_symbol(&Symbol::from(*k)));
+        }
+    }
+
+    #[test]
+    fn set_from_precomputed_parts() {
+        let _lock = test_lock();
+
+        let (seed, displacements, slots) = super::build_table(KEYWORDS);
+        let words: Vec<&str> = slots.iter().map(|&k| KEYWORDS[k]).collect();
+        let set = PerfectHashSymbolSet::from_parts(seed, &displacements, &words);
+
+        assert_eq!(set.len(), KEYWORDS.len());
+        for (i, w) in words.iter().enumerate() {
+            assert_eq!(set.index_of(w), Some(i));
+        }
+        assert!(!set.contains("function"));
+    }
+
+    #[test]
+    fn large_table() {
+        let keys: Vec<String> = (0..20_000).map(|i| format!("key{}", i)).collect();
+        let keys: Vec<&str> = keys.iter().map(|k| k.as_str()).collect();
+        let (seed, displacements, slots) = super::build_table(&keys);
+
+        let mut sorted = slots.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..keys.len()).collect::<Vec<_>>());
+        for (i, &k) in slots.iter().enumerate() {
+            let h = super::hashes(seed, keys[k]);
+            let (d1, d2) = displacements[h.g % displacements.len()];
+            assert_eq!(super::displace(h.f1, h.f2, d1 as usize, d2 as usize, keys.len()), i);
+        }
+    }
+
+    #[test]
+    fn empty_and_single_sets() {
+        let _lock = test_lock();
+
+        let empty = PerfectHashSymbolSet::builder().build();
+        assert!(empty.is_empty());
+        assert!(!empty.contains("a"));
+
+        let mut builder = PerfectHashSymbolSet::builder();
+        builder.insert("a".into()).insert("a".into());
+        let single = builder.build();
+        assert_eq!(single.len(), 1);
+        assert!(single.contains("a"));
+        assert!(!single.contains("b"));
+    }
+}
@@ -0,0 +1,115 @@
+use super::Symbol;
+
+use std::str::FromStr;
+
+/// Qualified name, pairing namespace (or prefix) with a local name.
+///
+/// Textual form is `ns:local`, or just `local` when the namespace is empty.
+/// Names are ordered by namespace first, then by local name.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QName {
+    pub ns: Symbol,
+    pub local: Symbol,
+}
+
+impl QName {
+    pub fn new<N: Into<Symbol>, L: Into<Symbol>>(ns: N, local: L) -> QName {
+        QName {
+            ns: ns.into(),
+            local: local.into(),
+        }
+    }
+
+    /// Creates name without namespace.
+    pub fn local<L: Into<Symbol>>(local: L) -> QName {
+        QName::new(Symbol::empty(), local)
+    }
+
+    /// Parses `ns:local` form, splitting at the first `:`.
+    pub fn parse(value: &str) -> QName {
+        match value.find(':') {
+            Some(i) => QName::new(&value[..i], &value[i + 1..]),
+            None => QName::local(value),
+        }
+    }
+
+    pub fn has_ns(&self) -> bool {
+        !self.ns.is_empty()
+    }
+}
+
+impl FromStr for QName {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(QName::parse(s))
+    }
+}
+
+impl<'a> From<&'a str> for QName {
+    fn from(value: &'a str) -> Self {
+        QName::parse(value)
+    }
+}
+
+impl std::fmt::Display for QName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.has_ns() {
+            write!(f, "{}:{}", self.ns, self.local)
+        } else {
+            std::fmt::Display::fmt(&self.local, f)
+        }
+    }
+}
+
+impl std::fmt::Debug for QName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.to_string())
+    }
+}
+
+impl serde::Serialize for QName {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for QName {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: serde::Deserializer<'de> {
+        Ok(QName::parse(&String::deserialize(deserializer)?))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn parse_and_display() {
+        let _lock = test_lock();
+
+        let q = QName::parse("xsd:string");
+        assert_eq!(q, QName::new("xsd", "string"));
+        assert_eq!(q.to_string(), "xsd:string");
+        assert_eq!("a:b:c".parse::<QName>().unwrap(), QName::new("a", "b:c"));
+
+        let l = QName::from("string");
+        assert!(!l.has_ns());
+        assert_eq!(l.to_string(), "string");
+        assert!(l < q);
+        assert!(QName::new("a", "z") < QName::new("b", "a"));
+        assert_eq!(format!("{:?}", q), "\"xsd:string\"");
+    }
+
+    #[test]
+    fn serde_roundtrip() {
+        let _lock = test_lock();
+
+        let q = QName::new("rdf", "type");
+        let json = serde_json::to_string(&q).unwrap();
+        assert_eq!(json, "\"rdf:type\"");
+        assert_eq!(serde_json::from_str::<QName>(&json).unwrap(), q);
+    }
+}
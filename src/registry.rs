@@ -0,0 +1,75 @@
+use super::Interner;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+lazy_static! {
+    static ref REGISTRY: RwLock<BTreeMap<String, Arc<Interner>>> = RwLock::new(BTreeMap::new());
+}
+
+impl Interner {
+    /// Returns interner registered under `name`, creating and registering a new one if necessary.
+    pub fn named(name: &str) -> Arc<Interner> {
+        if let Some(i) = REGISTRY.read().get(name) {
+            return i.clone();
+        }
+        REGISTRY.write().entry(name.to_string()).or_default().clone()
+    }
+
+    /// Returns interner registered under `name`, without creating a new one.
+    pub fn get_named(name: &str) -> Option<Arc<Interner>> {
+        REGISTRY.read().get(name).cloned()
+    }
+}
+
+/// Registers `interner` under `name`, returning interner previously registered under that name.
+pub fn register_interner(name: &str, interner: Interner) -> Option<Arc<Interner>> {
+    REGISTRY.write().insert(name.to_string(), Arc::new(interner))
+}
+
+/// Removes interner registered under `name` from the registry.
+/// Existing references to the interner remain valid.
+pub fn unregister_interner(name: &str) -> Option<Arc<Interner>> {
+    REGISTRY.write().remove(name)
+}
+
+/// Returns names of all registered interners, in sorted order.
+pub fn interner_names() -> Vec<String> {
+    REGISTRY.read().keys().cloned().collect()
+}
+
+/// Purges all registered interners, returning the total number of removed strings.
+pub fn purge_interners() -> usize {
+    let interners: Vec<Arc<Interner>> = REGISTRY.read().values().cloned().collect();
+    interners.iter().map(|i| i.purge()).sum()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    use std::sync::Arc;
+
+    #[test]
+    fn named_interners_are_shared_and_independent() {
+        let ast = Interner::named("registry-test-ast");
+        let ir = Interner::named("registry-test-ir");
+        assert!(Arc::ptr_eq(&ast, &Interner::named("registry-test-ast")));
+
+        let x = ast.intern("x");
+        ir.intern("x");
+        assert_eq!(Interner::get_named("registry-test-ast").unwrap().get("x"), Some(x));
+        assert_eq!(ast.stats().misses, 1);
+        assert_eq!(ir.purge(), 1);
+        assert_eq!(ast.len(), 1);
+
+        assert!(interner_names().contains(&"registry-test-ir".to_string()));
+        assert!(unregister_interner("registry-test-ir").is_some());
+        assert!(Interner::get_named("registry-test-ir").is_none());
+        assert!(register_interner("registry-test-ast", Interner::new()).is_some());
+        assert!(Interner::named("registry-test-ast").is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+use super::{Symbol, SymbolHashMap};
+
+use std::convert::TryFrom;
+
+/// Dictionary assigning dense ids to symbols, for exchanging symbol-heavy data between processes.
+///
+/// The sending side assigns ids with [`SymbolRemapper::id`] and periodically ships newly assigned
+/// entries obtained with [`SymbolRemapper::delta`]. The receiving side applies deltas with
+/// [`SymbolRemapper::apply`] and resolves incoming ids to locally interned symbols.
+#[derive(Default)]
+pub struct SymbolRemapper {
+    ids: SymbolHashMap<u32>,
+    symbols: Vec<Symbol>,
+    exported: usize,
+}
+
+impl SymbolRemapper {
+    pub fn new() -> Self {
+        SymbolRemapper::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Returns id of symbol `s`, assigning the next free id if it has none.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of ids exceeds `u32::MAX`.
+    pub fn id(&mut self, s: &Symbol) -> u32 {
+        if let Some(&id) = self.ids.get(s) {
+            return id;
+        }
+        self.push(s.clone())
+    }
+
+    /// Returns id of symbol `s`, without assigning a new one.
+    pub fn get_id(&self, s: &Symbol) -> Option<u32> {
+        self.ids.get(s).cloned()
+    }
+
+    pub fn resolve(&self, id: u32) -> Option<&Symbol> {
+        self.symbols.get(id as usize)
+    }
+
+    /// Returns entries assigned since the previous call, and marks them as exported.
+    pub fn delta(&mut self) -> SymbolDelta {
+        let delta = SymbolDelta {
+            first_id: self.exported as u32,
+            symbols: self.symbols[self.exported..].to_vec(),
+        };
+        self.exported = self.symbols.len();
+        delta
+    }
+
+    /// Returns all entries of the dictionary, without changing the exported mark.
+    pub fn snapshot(&self) -> SymbolDelta {
+        SymbolDelta {
+            first_id: 0,
+            symbols: self.symbols.clone(),
+        }
+    }
+
+    /// Appends entries received from the sending side. Deltas must be applied in order,
+    /// deltas already applied in full are ignored.
+    pub fn apply(&mut self, delta: &SymbolDelta) -> Result<(), RemapError> {
+        let first = delta.first_id as usize;
+        if first > self.symbols.len() {
+            return Err(RemapError {
+                expected: self.symbols.len() as u32,
+                found: delta.first_id,
+            });
+        }
+        for (i, s) in delta.symbols.iter().enumerate() {
+            if first + i < self.symbols.len() {
+                if self.symbols[first + i] != *s {
+                    return Err(RemapError {
+                        expected: self.symbols.len() as u32,
+                        found: delta.first_id,
+                    });
+                }
+            } else {
+                self.push(s.clone());
+            }
+        }
+        self.exported = self.symbols.len();
+        Ok(())
+    }
+
+    fn push(&mut self, s: Symbol) -> u32 {
+        let id = u32::try_from(self.symbols.len()).expect("symbol id overflow");
+        self.ids.insert(s.clone(), id);
+        self.symbols.push(s);
+        id
+    }
+}
+
+
+/// Contiguous range of dictionary entries, starting at id `first_id`.
+///
+/// Serialized as a `(first_id, symbols)` tuple.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolDelta {
+    first_id: u32,
+    symbols: Vec<Symbol>,
+}
+
+impl SymbolDelta {
+    pub fn new(first_id: u32, symbols: Vec<Symbol>) -> Self {
+        SymbolDelta {
+            first_id,
+            symbols,
+        }
+    }
+
+    pub fn first_id(&self) -> u32 {
+        self.first_id
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+impl serde::Serialize for SymbolDelta {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+        (self.first_id, &self.symbols).serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for SymbolDelta {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: serde::Deserializer<'de> {
+        let (first_id, symbols) = <(u32, Vec<Symbol>)>::deserialize(deserializer)?;
+        Ok(SymbolDelta::new(first_id, symbols))
+    }
+}
+
+
+/// Error returned when a [`SymbolDelta`] does not continue the receiving dictionary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemapError {
+    expected: u32,
+    found: u32,
+}
+
+impl RemapError {
+    /// Returns id the next delta was expected to start at.
+    pub fn expected(&self) -> u32 {
+        self.expected
+    }
+
+    /// Returns id the rejected delta starts at.
+    pub fn found(&self) -> u32 {
+        self.found
+    }
+}
+
+impl std::fmt::Display for RemapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "symbol delta starting at id {} does not match dictionary of length {}", self.found, self.expected)
+    }
+}
+
+impl std::error::Error for RemapError {}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn deltas_round_trip() {
+        let _lock = test_lock();
+
+        let mut tx = SymbolRemapper::new();
+        let mut rx = SymbolRemapper::new();
+
+        let ids: Vec<u32> = ["a", "b", "a"].iter().map(|s| tx.id(&Symbol::from(*s))).collect();
+        assert_eq!(ids, [0, 1, 0]);
+        let d1 = tx.delta();
+        assert_eq!(d1.first_id(), 0);
+        assert_eq!(d1.symbols().len(), 2);
+
+        assert_eq!(tx.id(&Symbol::from("c")), 2);
+        let d2: SymbolDelta = serde_json::from_str(&serde_json::to_string(&tx.delta()).unwrap()).unwrap();
+        assert_eq!(d2, SymbolDelta::new(2, vec![Symbol::from("c")]));
+        assert!(tx.delta().is_empty());
+
+        assert_eq!(rx.apply(&d2), Err(RemapError { expected: 0, found: 2 }));
+        rx.apply(&d1).unwrap();
+        rx.apply(&d1).unwrap();
+        rx.apply(&d2).unwrap();
+        assert_eq!(rx.resolve(2).map(|s| s.as_str()), Some("c"));
+        assert_eq!(rx.get_id(&Symbol::from("b")), Some(1));
+        assert_eq!(rx.resolve(3), None);
+        assert_eq!(rx.snapshot(), tx.snapshot());
+    }
+}
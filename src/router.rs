@@ -0,0 +1,203 @@
+use super::{Symbol, SymbolHashMap, SymbolMap};
+
+/// Routing table matching sequences of symbol segments against patterns like `/users/:id/posts/*`.
+///
+/// Pattern segments are matched in order of precedence: static segments first, then parameter
+/// segments (`:name`) capturing a single segment, then a trailing wildcard (`*`) capturing
+/// all remaining segments.
+pub struct SymbolRouter<V> {
+    root: Node<V>,
+}
+
+struct Node<V> {
+    value: Option<V>,
+    statics: SymbolHashMap<Node<V>>,
+    param: Option<Box<(Symbol, Node<V>)>>,
+    wildcard: Option<V>,
+}
+
+impl<V> Node<V> {
+    fn new() -> Self {
+        Node {
+            value: None,
+            statics: SymbolHashMap::default(),
+            param: None,
+            wildcard: None,
+        }
+    }
+
+    fn find<'a>(&'a self, path: &[Symbol], params: &mut Vec<(&'a Symbol, Symbol)>) -> Option<(&'a V, usize)> {
+        let (segment, tail) = match path.split_first() {
+            Some(s) => s,
+            None => {
+                return match (&self.value, &self.wildcard) {
+                    (Some(v), _) | (None, Some(v)) => Some((v, 0)),
+                    (None, None) => None,
+                };
+            }
+        };
+        if let Some(r) = self.statics.get(segment).and_then(|n| n.find(tail, params)) {
+            return Some(r);
+        }
+        if let Some(p) = self.param.as_ref() {
+            params.push((&p.0, segment.clone()));
+            if let Some(r) = p.1.find(tail, params) {
+                return Some(r);
+            }
+            params.pop();
+        }
+        self.wildcard.as_ref().map(|v| (v, path.len()))
+    }
+}
+
+impl<V> SymbolRouter<V> {
+    pub fn new() -> Self {
+        SymbolRouter {
+            root: Node::new(),
+        }
+    }
+
+    /// Adds route for `pattern`, returning value previously registered for the same pattern.
+    /// Empty segments in the pattern are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if wildcard is not the last segment of the pattern, or if a parameter segment
+    /// conflicts with a differently named parameter registered at the same position.
+    pub fn insert(&mut self, pattern: &str, value: V) -> Option<V> {
+        let mut segments = pattern.split('/').filter(|s| !s.is_empty()).peekable();
+        let mut node = &mut self.root;
+        while let Some(segment) = segments.next() {
+            if segment == "*" {
+                assert!(segments.peek().is_none(), "wildcard must be the last segment of pattern {:?}", pattern);
+                return node.wildcard.replace(value);
+            }
+            node = if let Some(name) = segment.strip_prefix(':') {
+                let name = Symbol::from(name);
+                let p = node.param.get_or_insert_with(|| Box::new((name.clone(), Node::new())));
+                assert!(p.0 == name, "parameter :{} conflicts with :{} in pattern {:?}", name, p.0, pattern);
+                &mut p.1
+            } else {
+                node.statics.entry(Symbol::from(segment)).or_insert_with(Node::new)
+            };
+        }
+        node.value.replace(value)
+    }
+
+    /// Finds route matching `path`, capturing parameter and wildcard segments.
+    pub fn route(&self, path: &[Symbol]) -> Option<RouteMatch<'_, V>> {
+        let mut params = Vec::new();
+        self.root.find(path, &mut params).map(|(value, rest)| {
+            let mut captured = SymbolMap::with_capacity(params.len());
+            for (name, segment) in params {
+                captured.insert(name.clone(), segment);
+            }
+            RouteMatch {
+                value,
+                params: captured,
+                rest: path[path.len() - rest..].to_vec(),
+            }
+        })
+    }
+
+    /// Same as [`SymbolRouter::route`], but splits `path` into segments on `/`.
+    pub fn route_str(&self, path: &str) -> Option<RouteMatch<'_, V>> {
+        let path: Vec<Symbol> = path.split('/').filter(|s| !s.is_empty()).map(Symbol::from).collect();
+        self.route(&path)
+    }
+}
+
+impl<V> Default for SymbolRouter<V> {
+    fn default() -> Self {
+        SymbolRouter::new()
+    }
+}
+
+
+/// Result of a successful [`SymbolRouter`] lookup.
+#[derive(Debug)]
+pub struct RouteMatch<'a, V> {
+    value: &'a V,
+    params: SymbolMap<Symbol>,
+    rest: Vec<Symbol>,
+}
+
+impl<'a, V> RouteMatch<'a, V> {
+    pub fn value(&self) -> &'a V {
+        self.value
+    }
+
+    /// Returns captured parameter segments, keyed by parameter name, in pattern order.
+    pub fn params(&self) -> &SymbolMap<Symbol> {
+        &self.params
+    }
+
+    /// Returns segments captured by the trailing wildcard, empty if route has no wildcard.
+    pub fn rest(&self) -> &[Symbol] {
+        &self.rest
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    fn router() -> SymbolRouter<u32> {
+        let mut r = SymbolRouter::new();
+        r.insert("/users", 1);
+        r.insert("/users/:id", 2);
+        r.insert("/users/me", 3);
+        r.insert("/users/:id/posts/*", 4);
+        r.insert("/static/*", 5);
+        r
+    }
+
+    #[test]
+    fn static_segments_take_precedence() {
+        let _lock = test_lock();
+
+        let r = router();
+        assert_eq!(r.route_str("/users").map(|m| *m.value()), Some(1));
+        assert_eq!(r.route_str("/users/me").map(|m| *m.value()), Some(3));
+        assert_eq!(r.route_str("/posts").map(|m| *m.value()), None);
+    }
+
+    #[test]
+    fn parameters_and_wildcards_are_captured() {
+        let _lock = test_lock();
+
+        let r = router();
+        let m = r.route_str("/users/42").unwrap();
+        assert_eq!(*m.value(), 2);
+        assert_eq!(m.params().get("id").map(|s| s.as_str()), Some("42"));
+
+        let m = r.route_str("/users/me/posts/2021/05").unwrap();
+        assert_eq!(*m.value(), 4);
+        assert_eq!(m.params().get("id").map(|s| s.as_str()), Some("me"));
+        assert_eq!(m.rest(), [Symbol::from("2021"), Symbol::from("05")]);
+
+        let m = r.route_str("/static").unwrap();
+        assert_eq!(*m.value(), 5);
+        assert!(m.rest().is_empty());
+    }
+
+    #[test]
+    fn insert_replaces_previous_value() {
+        let _lock = test_lock();
+
+        let mut r = router();
+        assert_eq!(r.insert("users/:id/", 6), Some(2));
+        assert_eq!(r.route_str("/users/7").map(|m| *m.value()), Some(6));
+    }
+
+    #[test]
+    #[should_panic]
+    fn conflicting_parameter_names_panic() {
+        let _lock = test_lock();
+
+        let mut r = router();
+        r.insert("/users/:uid/likes", 7);
+    }
+}
@@ -0,0 +1,99 @@
+use super::Symbol;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type Record = Rc<RefCell<Vec<Symbol>>>;
+
+thread_local! {
+    /// Records of scoped interners active on the current thread, innermost last.
+    static SCOPES: RefCell<Vec<Record>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Guard keeping symbols first interned by the current thread while it is alive.
+///
+/// The guard holds a reference to every symbol created within its scope, and releases them
+/// when dropped, so that symbols not referenced elsewhere are removed from the table at that point.
+/// Symbols created within nested scopes are held by all of them, until the outermost one is dropped.
+/// This helps keeping tests and batch jobs from polluting the global symbol table.
+pub struct ScopedInterner {
+    record: Record,
+}
+
+impl ScopedInterner {
+    pub fn new() -> ScopedInterner {
+        let record = Record::default();
+        SCOPES.with(|s| s.borrow_mut().push(record.clone()));
+        ScopedInterner { record }
+    }
+
+    /// Returns number of symbols created within the scope so far.
+    pub fn created(&self) -> usize {
+        self.record.borrow().len()
+    }
+
+    /// Returns symbols created within the scope so far.
+    pub fn symbols(&self) -> Vec<Symbol> {
+        self.record.borrow().clone()
+    }
+}
+
+impl Default for ScopedInterner {
+    fn default() -> Self {
+        ScopedInterner::new()
+    }
+}
+
+impl Drop for ScopedInterner {
+    fn drop(&mut self) {
+        // recorded symbols are released when the record is dropped along with the guard
+        let _ = SCOPES.try_with(|s| s.borrow_mut().retain(|r| !Rc::ptr_eq(r, &self.record)));
+    }
+}
+
+impl std::fmt::Debug for ScopedInterner {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ScopedInterner")
+            .field("created", &self.created())
+            .finish()
+    }
+}
+
+/// Records newly created symbol in all scopes active on the current thread.
+#[inline]
+pub(crate) fn record(s: &Symbol) {
+    let _ = SCOPES.try_with(|scopes| {
+        for r in scopes.borrow().iter() {
+            r.borrow_mut().push(s.clone());
+        }
+    });
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn scope_records_created_symbols() {
+        let _lock = test_lock();
+
+        let existing = Symbol::new("existing");
+        let outer = ScopedInterner::new();
+        let kept = Symbol::new("kept");
+        {
+            let inner = ScopedInterner::new();
+            drop(Symbol::new("temporary"));
+            let _e = Symbol::new("existing");
+            assert_eq!(inner.created(), 1);
+            assert_eq!(inner.symbols(), ["temporary"]);
+        }
+        assert_eq!(outer.created(), 2);
+        assert!(Symbol::get("temporary").is_some());
+        drop(outer);
+        assert_eq!(Symbol::get("temporary"), None);
+        assert_eq!(Symbol::get("kept"), Some(kept));
+        drop(existing);
+    }
+}
@@ -0,0 +1,202 @@
+use super::{Keys, Symbol, SymbolMap};
+
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
+use heapsize::HeapSizeOf;
+
+/// Set of symbols preserving insertion order.
+#[derive(Clone, Default)]
+pub struct SymbolSet(SymbolMap<()>);
+
+impl SymbolSet {
+    pub fn new() -> Self {
+        SymbolSet(SymbolMap::new())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        SymbolSet(SymbolMap::with_capacity(capacity))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.len() == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+        where Q: ?Sized + AsRef<str> + Hash + Eq
+    {
+        self.0.get(value).is_some()
+    }
+
+    /// Adds symbol to the set, returning `false` if it was already present.
+    pub fn insert(&mut self, value: Symbol) -> bool {
+        self.0.insert(value, ()).is_none()
+    }
+
+    /// Removes symbol from the set, returning `true` if it was present.
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+        where Q: ?Sized + AsRef<str> + Hash + Eq
+    {
+        self.0.remove(value).is_some()
+    }
+
+    pub fn iter(&self) -> Keys<'_, ()> {
+        self.0.keys()
+    }
+
+    /// Returns symbols present in `self` or `other`, in order of `self` followed by new symbols of `other`.
+    pub fn union(&self, other: &SymbolSet) -> SymbolSet {
+        self.iter().chain(other.iter().filter(|s| !self.0.contains_sym(s))).cloned().collect()
+    }
+
+    /// Returns symbols present in both `self` and `other`, in order of `self`.
+    pub fn intersection(&self, other: &SymbolSet) -> SymbolSet {
+        self.iter().filter(|s| other.0.contains_sym(s)).cloned().collect()
+    }
+
+    /// Returns symbols present in `self` but not in `other`, in order of `self`.
+    pub fn difference(&self, other: &SymbolSet) -> SymbolSet {
+        self.iter().filter(|s| !other.0.contains_sym(s)).cloned().collect()
+    }
+
+    /// Returns symbols present in exactly one of `self` and `other`,
+    /// in order of `self` followed by new symbols of `other`.
+    pub fn symmetric_difference(&self, other: &SymbolSet) -> SymbolSet {
+        self.iter().filter(|s| !other.0.contains_sym(s))
+            .chain(other.iter().filter(|s| !self.0.contains_sym(s)))
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_subset(&self, other: &SymbolSet) -> bool {
+        self.len() <= other.len() && self.iter().all(|s| other.0.contains_sym(s))
+    }
+}
+
+impl PartialEq for SymbolSet {
+    /// Sets are equal if they contain the same symbols, regardless of order.
+    fn eq(&self, other: &SymbolSet) -> bool {
+        self.len() == other.len() && self.is_subset(other)
+    }
+}
+
+impl Eq for SymbolSet {}
+
+impl BitOr<&SymbolSet> for &SymbolSet {
+    type Output = SymbolSet;
+
+    fn bitor(self, rhs: &SymbolSet) -> SymbolSet {
+        self.union(rhs)
+    }
+}
+
+impl BitAnd<&SymbolSet> for &SymbolSet {
+    type Output = SymbolSet;
+
+    fn bitand(self, rhs: &SymbolSet) -> SymbolSet {
+        self.intersection(rhs)
+    }
+}
+
+impl Sub<&SymbolSet> for &SymbolSet {
+    type Output = SymbolSet;
+
+    fn sub(self, rhs: &SymbolSet) -> SymbolSet {
+        self.difference(rhs)
+    }
+}
+
+impl BitXor<&SymbolSet> for &SymbolSet {
+    type Output = SymbolSet;
+
+    fn bitxor(self, rhs: &SymbolSet) -> SymbolSet {
+        self.symmetric_difference(rhs)
+    }
+}
+
+impl FromIterator<Symbol> for SymbolSet {
+    fn from_iter<T: IntoIterator<Item = Symbol>>(iter: T) -> Self {
+        let mut set = SymbolSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Symbol> for SymbolSet {
+    fn extend<T: IntoIterator<Item = Symbol>>(&mut self, iter: T) {
+        for s in iter {
+            self.insert(s);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a SymbolSet {
+    type Item = &'a Symbol;
+    type IntoIter = Keys<'a, ()>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl std::fmt::Debug for SymbolSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl HeapSizeOf for SymbolSet {
+    fn heap_size_of_children(&self) -> usize {
+        self.0.heap_size_of_children()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    fn set(items: &[&str]) -> SymbolSet {
+        items.iter().map(|s| Symbol::from(*s)).collect()
+    }
+
+    fn names(set: &SymbolSet) -> Vec<&str> {
+        set.iter().map(|s| s.as_str()).collect()
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        let _lock = test_lock();
+
+        let a = set(&["read", "write", "exec"]);
+        let b = set(&["admin", "write", "read"]);
+
+        assert_eq!(names(&(&a | &b)), ["read", "write", "exec", "admin"]);
+        assert_eq!(names(&(&a & &b)), ["read", "write"]);
+        assert_eq!(names(&(&a - &b)), ["exec"]);
+        assert_eq!(names(&(&a ^ &b)), ["exec", "admin"]);
+        assert_eq!(&a & &b, set(&["write", "read"]));
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let _lock = test_lock();
+
+        let mut s = SymbolSet::new();
+        assert!(s.insert(Symbol::from("a")));
+        assert!(!s.insert(Symbol::from("a")));
+        assert!(s.contains("a"));
+        assert!(s.remove("a"));
+        assert!(!s.remove("a"));
+        assert!(s.is_empty());
+    }
+}
@@ -0,0 +1,389 @@
+use super::{hooks, stats, BuildSymbolHasher, Symbol, SymbolHasher};
+
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+use std::iter::FromIterator;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+const DEFAULT_SHARD_COUNT: usize = 16;
+
+/// Flag set in `SHARD_COUNT` once the global symbol table is initialized.
+const INITIALIZED: usize = 1 << (usize::BITS - 1);
+
+static SHARD_COUNT: AtomicUsize = AtomicUsize::new(DEFAULT_SHARD_COUNT);
+
+/// Sets number of shards of the global symbol table, rounded up to a power of two.
+///
+/// Must be called before the first symbol is created, returns `false` if the table
+/// has already been initialized.
+pub fn set_shard_count(count: usize) -> bool {
+    let count = count.max(1).next_power_of_two();
+    SHARD_COUNT.fetch_update(Ordering::AcqRel, Ordering::Acquire, |c| {
+        if c & INITIALIZED == 0 { Some(count) } else { None }
+    }).is_ok()
+}
+
+/// Returns number of shards of the global symbol table.
+pub fn shard_count() -> usize {
+    SHARD_COUNT.load(Ordering::Acquire) & !INITIALIZED
+}
+
+/// Hash function used by the global symbol table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableHasher {
+    /// Randomly seeded SipHash, resistant against collision attacks.
+    Random,
+    /// [`SymbolHasher`] (FxHash), much faster, but should not be used for untrusted input.
+    Fx,
+}
+
+impl TableHasher {
+    #[cfg(feature = "deterministic")]
+    const DEFAULT: TableHasher = TableHasher::Fx;
+
+    #[cfg(not(feature = "deterministic"))]
+    const DEFAULT: TableHasher = TableHasher::Random;
+
+    fn from_usize(value: usize) -> TableHasher {
+        if value & !INITIALIZED == TableHasher::Fx as usize { TableHasher::Fx } else { TableHasher::Random }
+    }
+}
+
+static TABLE_HASHER: AtomicUsize = AtomicUsize::new(TableHasher::DEFAULT as usize);
+
+/// Sets hash function used by the global symbol table. Defaults to [`TableHasher::Fx`]
+/// with `deterministic` feature enabled, [`TableHasher::Random`] otherwise.
+///
+/// Must be called before the first symbol is created, returns `false` if the table
+/// has already been initialized.
+pub fn set_table_hasher(hasher: TableHasher) -> bool {
+    TABLE_HASHER.fetch_update(Ordering::AcqRel, Ordering::Acquire, |h| {
+        if h & INITIALIZED == 0 { Some(hasher as usize) } else { None }
+    }).is_ok()
+}
+
+/// Returns hash function used by the global symbol table.
+pub fn table_hasher() -> TableHasher {
+    TableHasher::from_usize(TABLE_HASHER.load(Ordering::Acquire))
+}
+
+/// Hasher builder of the global symbol table, selected with [`set_table_hasher`].
+pub(crate) enum TableHashBuilder {
+    Random(RandomState),
+    Fx,
+}
+
+static TABLE_HASH_BUILDER: OnceLock<TableHashBuilder> = OnceLock::new();
+
+/// Returns hash of `value` used by the global symbol table. All shards share the same seed,
+/// so the hash can be computed once and stored in the symbol header.
+#[inline]
+pub(crate) fn table_hash(value: &str) -> u64 {
+    let builder = TABLE_HASH_BUILDER.get_or_init(|| match table_hasher() {
+        TableHasher::Random => TableHashBuilder::Random(RandomState::new()),
+        TableHasher::Fx => TableHashBuilder::Fx,
+    });
+    builder.hash_one(value)
+}
+
+impl BuildHasher for TableHashBuilder {
+    type Hasher = TableHashState;
+
+    #[inline]
+    fn build_hasher(&self) -> TableHashState {
+        match self {
+            TableHashBuilder::Random(r) => TableHashState::Random(r.build_hasher()),
+            TableHashBuilder::Fx => TableHashState::Fx(SymbolHasher::default()),
+        }
+    }
+}
+
+pub(crate) enum TableHashState {
+    Random(DefaultHasher),
+    Fx(SymbolHasher),
+}
+
+impl Hasher for TableHashState {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        match self {
+            TableHashState::Random(h) => h.write(bytes),
+            TableHashState::Fx(h) => h.write(bytes),
+        }
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        match self {
+            TableHashState::Random(h) => h.write_u8(i),
+            TableHashState::Fx(h) => h.write_u8(i),
+        }
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        match self {
+            TableHashState::Random(h) => h.finish(),
+            TableHashState::Fx(h) => h.finish(),
+        }
+    }
+}
+
+/// Hasher of shard tables, which only passes through table hash of the entry.
+#[derive(Default)]
+pub(crate) struct StoredHasher(u64);
+
+impl Hasher for StoredHasher {
+    fn write(&mut self, _bytes: &[u8]) {
+        unreachable!("shard table entries are hashed with write_u64")
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.0 = i;
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Shard table entry, hashed by the table hash stored in the symbol header, so that growing
+/// the table does not hash the strings again.
+struct TableEntry(Symbol);
+
+impl Hash for TableEntry {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.0.header().table_hash)
+    }
+}
+
+impl PartialEq for TableEntry {
+    #[inline]
+    fn eq(&self, other: &TableEntry) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Eq for TableEntry {}
+
+/// String key used to look up shard table entries.
+#[repr(transparent)]
+#[derive(PartialEq, Eq)]
+struct TableKey(str);
+
+impl TableKey {
+    #[inline]
+    fn new(value: &str) -> &TableKey {
+        // TableKey is #[repr(transparent)] over str
+        unsafe { &*(value as *const str as *const TableKey) }
+    }
+}
+
+impl Hash for TableKey {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(table_hash(&self.0))
+    }
+}
+
+impl Borrow<TableKey> for TableEntry {
+    #[inline]
+    fn borrow(&self) -> &TableKey {
+        TableKey::new(self.0.as_str())
+    }
+}
+
+/// Table of a single shard. Entries do not own a reference to their symbols.
+#[derive(Default)]
+pub(crate) struct SymbolTable(HashSet<TableEntry, BuildHasherDefault<StoredHasher>>);
+
+impl SymbolTable {
+    #[inline]
+    pub(crate) fn get(&self, value: &str) -> Option<&Symbol> {
+        self.0.get(TableKey::new(value)).map(|e| &e.0)
+    }
+
+    #[inline]
+    pub(crate) fn take(&mut self, value: &str) -> Option<Symbol> {
+        self.0.take(TableKey::new(value)).map(|e| e.0)
+    }
+
+    #[inline]
+    pub(crate) fn insert(&mut self, s: Symbol) -> bool {
+        self.0.insert(TableEntry(s))
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Symbol> {
+        self.0.iter().map(|e| &e.0)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit()
+    }
+}
+
+impl FromIterator<Symbol> for SymbolTable {
+    fn from_iter<I: IntoIterator<Item = Symbol>>(iter: I) -> Self {
+        SymbolTable(iter.into_iter().map(TableEntry).collect())
+    }
+}
+
+/// Independently locked shard of the global symbol table.
+pub(crate) struct Shard(RwLock<SymbolTable>);
+
+impl Shard {
+    #[inline]
+    pub(crate) fn read(&self) -> RwLockReadGuard<'_, SymbolTable> {
+        hooks::check_reentrancy();
+        match self.0.try_read() {
+            Some(guard) => guard,
+            None => stats::lock_wait(|| self.0.read()),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn write(&self) -> RwLockWriteGuard<'_, SymbolTable> {
+        hooks::check_reentrancy();
+        match self.0.try_write() {
+            Some(guard) => guard,
+            None => stats::lock_wait(|| self.0.write()),
+        }
+    }
+}
+
+/// Global symbol table, split into independently locked shards selected by string hash.
+pub(crate) struct SymbolShards {
+    shards: Box<[Shard]>,
+}
+
+impl SymbolShards {
+    pub(crate) fn new() -> Self {
+        TABLE_HASHER.fetch_or(INITIALIZED, Ordering::AcqRel);
+        // hasher is selected once, before any symbol is allocated
+        table_hash("");
+        let count = SHARD_COUNT.fetch_or(INITIALIZED, Ordering::AcqRel) & !INITIALIZED;
+        SymbolShards {
+            shards: (0..count).map(|_| Shard(RwLock::new(SymbolTable::default()))).collect(),
+        }
+    }
+
+    #[inline]
+    fn index(&self, value: &str) -> usize {
+        self.index_hashed(BuildSymbolHasher::default().hash_one(value))
+    }
+
+    #[inline]
+    fn index_hashed(&self, hash: u64) -> usize {
+        // high bits are used, since low bits select buckets within the shard table
+        (hash >> 32) as usize & (self.shards.len() - 1)
+    }
+
+    #[inline]
+    pub(crate) fn shard(&self, value: &str) -> &Shard {
+        &self.shards[self.index(value)]
+    }
+
+    /// Returns shard for a string with precomputed [`BuildSymbolHasher`] hash.
+    #[inline]
+    pub(crate) fn shard_hashed(&self, hash: u64) -> &Shard {
+        &self.shards[self.index_hashed(hash)]
+    }
+
+    /// Returns total number of symbols, locking one shard at a time.
+    pub(crate) fn len(&self) -> usize {
+        self.shards.iter().map(|s| s.read().len()).sum()
+    }
+
+    /// Returns all symbols, locking one shard at a time.
+    pub(crate) fn symbols(&self) -> Vec<Symbol> {
+        let mut symbols = Vec::with_capacity(self.len());
+        for s in self.shards.iter() {
+            symbols.extend(s.read().iter().filter_map(Symbol::upgrade));
+        }
+        symbols
+    }
+
+    /// Returns symbols with strings matching `pred`, locking one shard at a time.
+    pub(crate) fn symbols_matching<F: Fn(&str) -> bool>(&self, pred: F) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+        for s in self.shards.iter() {
+            symbols.extend(s.read().iter().filter(|s| pred(s.as_str())).filter_map(Symbol::upgrade));
+        }
+        symbols
+    }
+
+    /// Locks all shards for writing, always in the same order.
+    pub(crate) fn write_all(&self) -> Vec<RwLockWriteGuard<'_, SymbolTable>> {
+        self.shards.iter().map(|s| s.write()).collect()
+    }
+
+    /// Interns normalized `values`, locking each shard at most once.
+    pub(crate) fn intern_all<S: AsRef<str>>(&self, values: &[S]) -> Vec<Symbol> {
+        let mut by_shard: Vec<Vec<usize>> = vec![Vec::new(); self.shards.len()];
+        for (i, v) in values.iter().enumerate() {
+            by_shard[self.index(v.as_ref())].push(i);
+        }
+        let mut symbols: Vec<Option<Symbol>> = vec![None; values.len()];
+        for (shard, indices) in self.shards.iter().zip(by_shard) {
+            if indices.is_empty() {
+                continue;
+            }
+            let mut table = shard.write();
+            for i in indices {
+                symbols[i] = Some(Symbol::intern(&mut table, values[i].as_ref()));
+            }
+        }
+        symbols.into_iter().map(Option::unwrap).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn shard_count_is_fixed_after_initialization() {
+        let _lock = test_lock();
+
+        assert!(shard_count().is_power_of_two());
+        assert!(!set_shard_count(4));
+        assert!(!set_table_hasher(TableHasher::Fx));
+    }
+
+    #[test]
+    fn batch_interning_matches_single() {
+        let _lock = test_lock();
+
+        let values = ["a", "b", "c", "a", "d", "e", "f", "g", "h"];
+        let symbols = super::super::SYMBOLS.intern_all(&values);
+        for (v, s) in values.iter().zip(symbols.iter()) {
+            assert_eq!(Symbol::get(v).as_ref(), Some(s));
+        }
+        assert_eq!(symbols[0], symbols[3]);
+    }
+
+    #[test]
+    fn table_grows_using_stored_hashes() {
+        let _lock = test_lock();
+
+        let symbols: Vec<Symbol> = (0..1000).map(|i| Symbol::new(format!("grow{}", i))).collect();
+        for s in symbols.iter() {
+            assert_eq!(s.header().table_hash, super::table_hash(s));
+            assert_eq!(Symbol::get(s.as_str()).as_ref(), Some(s));
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use super::{hooks, id, leak_report, stats, Symbol, SYMBOLS};
+
+use std::mem::ManuallyDrop;
+use std::sync::atomic::Ordering;
+
+/// Frees permanent symbols and releases memory of the global symbol table, so that leak checkers
+/// like Valgrind or ASAN do not report them.
+///
+/// Pinned symbols, held by process-lifetime statics (the empty symbol, static symbols,
+/// atoms and symbols of a frozen table), are never freed and remain valid after the shutdown.
+///
+/// # Safety
+///
+/// Must be called at the end of the program, when no other thread uses symbols.
+/// Handles to symbols made permanent with [`Symbol::permanent`] or [`Symbol::make_permanent`],
+/// and strings returned by [`Symbol::leak`], must not be used, cloned or dropped afterwards.
+/// Weak handles stay valid, but no longer upgrade.
+///
+/// # Panics
+///
+/// Panics if any symbol other than a permanent one is still referenced, see [`leak_report`].
+pub unsafe fn shutdown() {
+    let leaks = leak_report();
+    assert!(leaks.is_empty(), "cannot shut down symbol table, {} symbols are still referenced: {:?}", leaks.len(), leaks);
+    free_unpinned()
+}
+
+/// Same as [`shutdown`], but frees all symbols except pinned ones, even if they are still referenced.
+///
+/// # Safety
+///
+/// No unpinned symbol created before the shutdown may be used, cloned or dropped afterwards,
+/// and no other thread may use symbols while this function runs. With `thread-cache` feature,
+/// other threads which used symbols must have exited or called
+/// [`clear_thread_cache`](crate::clear_thread_cache).
+pub unsafe fn force_shutdown() {
+    #[cfg(feature = "thread-cache")]
+    super::clear_thread_cache();
+
+    free_unpinned()
+}
+
+unsafe fn free_unpinned() {
+    let mut shards = SYMBOLS.write_all();
+    for table in shards.iter_mut() {
+        // table entries do not own a reference
+        let freed: Vec<_> = table.iter()
+            .filter(|s| !s.header().pinned.load(Ordering::Relaxed))
+            .map(|s| ManuallyDrop::new(Symbol(s.0)))
+            .collect();
+        for s in freed {
+            std::mem::forget(table.take(s.as_str()));
+            s.free();
+        }
+        table.shrink_to_fit();
+    }
+
+    // advance generation, so that freed symbols are detected in debug builds, while pinned ones stay valid
+    #[cfg(debug_assertions)]
+    {
+        let generation = super::GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+        for s in shards.iter().flat_map(|t| t.iter()) {
+            (*(s.0.as_ptr() as *mut super::SymbolHdr)).generation = generation;
+        }
+    }
+}
+
+impl Symbol {
+    /// Destroys symbol regardless of outstanding strong references. Its memory is released
+    /// when the last weak handle is dropped.
+    unsafe fn free(&self) {
+        hooks::freed(self.as_str());
+        stats::destroyed();
+        // case siblings are other table entries, freed on their own
+        #[cfg(feature = "casefold")]
+        std::mem::forget(super::fold::take_companions(self));
+
+        let hdr = self.header();
+        id::release_id(hdr.id);
+        // weak handles must no longer upgrade
+        hdr.permanent.store(false, Ordering::Relaxed);
+        hdr.ref_count.store(0, Ordering::Relaxed);
+        Symbol::release_weak(self.0);
+    }
+}
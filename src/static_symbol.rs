@@ -0,0 +1,130 @@
+use super::{hash_str, Symbol};
+
+use std::sync::OnceLock;
+
+#[cfg(feature = "macros")]
+pub use kg_symbol_macros::symbol;
+
+/// Symbol for a string known at compile time, interned on first use.
+///
+/// Interned symbol is made permanent and cached, so [`StaticSymbol::get`] only touches
+/// the global symbol table once. Hash of the string is computed with [`hash_str`] in `new`,
+/// which for statics happens at compile time. Usually created with `symbol!` macro
+/// (`macros` feature).
+pub struct StaticSymbol {
+    value: &'static str,
+    hash: u64,
+    symbol: OnceLock<Symbol>,
+}
+
+impl StaticSymbol {
+    pub const fn new(value: &'static str) -> Self {
+        StaticSymbol {
+            value,
+            hash: hash_str(value),
+            symbol: OnceLock::new(),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.value
+    }
+
+    /// Returns [`BuildSymbolHasher`](crate::BuildSymbolHasher) hash of the string.
+    pub const fn precomputed_hash(&self) -> u64 {
+        self.hash
+    }
+
+    #[inline]
+    pub fn get(&self) -> Symbol {
+        self.symbol.get_or_init(|| {
+            let s = Symbol::new_hashed(self.value, self.hash);
+            s.pin();
+            s
+        }).clone()
+    }
+}
+
+/// Declares accessor functions for well-known symbols, each returning a permanent symbol
+/// interned on first use (see [`StaticSymbol`]).
+///
+/// ```
+/// kg_symbol::symbols! {
+///     pub FOO = "foo",
+///     BAR = "bar",
+/// }
+///
+/// assert_eq!(FOO(), "foo");
+/// assert_eq!(BAR(), kg_symbol::Symbol::from("bar"));
+/// ```
+#[macro_export]
+macro_rules! symbols {
+    ($($(#[$attr:meta])* $vis:vis $name:ident = $value:expr),* $(,)?) => {
+        $(
+            $(#[$attr])*
+            #[allow(non_snake_case)]
+            #[inline]
+            $vis fn $name() -> $crate::Symbol {
+                static SYMBOL: $crate::StaticSymbol = $crate::StaticSymbol::new($value);
+                SYMBOL.get()
+            }
+        )*
+    };
+}
+
+impl std::fmt::Debug for StaticSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self.value, f)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    static KEYWORD: StaticSymbol = StaticSymbol::new("static keyword");
+
+    symbols! {
+        /// Documented symbol.
+        DECLARED = "declared symbol",
+        pub(crate) OTHER_DECLARED = concat!("other ", "declared symbol"),
+    }
+
+    #[test]
+    fn static_symbol_is_interned_once() {
+        let _lock = test_lock();
+
+        let s = KEYWORD.get();
+        assert!(s.is_permanent());
+        assert_eq!(s, Symbol::new("static keyword"));
+        assert_eq!(KEYWORD.get(), s);
+
+        let d = DECLARED();
+        assert!(d.is_permanent());
+        assert_eq!(d, "declared symbol");
+        assert_eq!(DECLARED(), d);
+        assert_eq!(OTHER_DECLARED(), Symbol::new("other declared symbol"));
+
+        #[cfg(feature = "macros")]
+        {
+            let m = symbol!("static keyword");
+            assert_eq!(m, s);
+            assert_eq!(symbol!("tab\t\u{1F600}"), Symbol::new("tab\t\u{1F600}"));
+            assert_eq!(symbol!(r#"raw "quoted""#), "raw \"quoted\"");
+            // symbols interned with a wrong precomputed hash would be placed in a wrong shard
+            let escaped = symbol!("\x41\\\"\0 zażółć");
+            assert_eq!(Symbol::get("A\\\"\0 zażółć"), Some(escaped));
+        }
+
+        static ESCAPED: StaticSymbol = StaticSymbol::new("tab\t\u{1F600} zażółć");
+        assert_eq!(ESCAPED.precomputed_hash(), std::hash::BuildHasher::hash_one(&BuildSymbolHasher::default(), "tab\t\u{1F600} zażółć"));
+
+        // permanent symbols outlive the test, remove them from the table to keep it clean for other tests
+        for v in ["static keyword", "declared symbol", "other declared symbol", "tab\t\u{1F600}", "raw \"quoted\"", "A\\\"\0 zażółć"].iter() {
+            let mut shard = SYMBOLS.shard(v).write();
+            std::mem::forget(shard.take(v));
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+static CREATED: AtomicU64 = AtomicU64::new(0);
+static FREED: AtomicU64 = AtomicU64::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Usage statistics of the global symbol table.
+///
+/// With `metrics` feature enabled, the same statistics are also published through
+/// the [`metrics`](https://docs.rs/metrics) facade as `symbols_live` and `bytes_in_use` gauges
+/// and `symbols_created_total` counter, along with `lock_wait_seconds` histogram of time spent
+/// waiting for contended table locks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SymbolStats {
+    /// Number of symbols currently alive.
+    pub live: u64,
+    /// Total number of symbols created.
+    pub created: u64,
+    /// Total number of symbols destroyed.
+    pub freed: u64,
+    /// Number of bytes allocated for symbols, including headers.
+    pub bytes: usize,
+}
+
+/// Returns usage statistics of the global symbol table.
+pub fn symbol_stats() -> SymbolStats {
+    let freed = FREED.load(Ordering::Relaxed);
+    let created = CREATED.load(Ordering::Relaxed);
+    SymbolStats {
+        live: created.saturating_sub(freed),
+        created,
+        freed,
+        bytes: BYTES.load(Ordering::Relaxed),
+    }
+}
+
+#[inline]
+pub(crate) fn allocated(size: usize) {
+    CREATED.fetch_add(1, Ordering::Relaxed);
+    BYTES.fetch_add(size, Ordering::Relaxed);
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!("symbols_created_total").increment(1);
+        metrics::gauge!("symbols_live").increment(1.0);
+        metrics::gauge!("bytes_in_use").increment(size as f64);
+    }
+}
+
+#[inline]
+pub(crate) fn destroyed() {
+    FREED.fetch_add(1, Ordering::Relaxed);
+    #[cfg(feature = "metrics")]
+    metrics::gauge!("symbols_live").decrement(1.0);
+}
+
+#[inline]
+pub(crate) fn deallocated(size: usize) {
+    BYTES.fetch_sub(size, Ordering::Relaxed);
+    #[cfg(feature = "metrics")]
+    metrics::gauge!("bytes_in_use").decrement(size as f64);
+}
+
+/// Acquires contended table lock with `lock`. With `metrics` feature enabled, time spent waiting
+/// is recorded in `lock_wait_seconds` histogram, and with `tracing` feature it is reported as an event.
+#[cold]
+pub(crate) fn lock_wait<G, F: FnOnce() -> G>(lock: F) -> G {
+    #[cfg(any(feature = "metrics", feature = "tracing"))]
+    let start = std::time::Instant::now();
+    let guard = lock();
+    #[cfg(any(feature = "metrics", feature = "tracing"))]
+    {
+        let wait = start.elapsed();
+        #[cfg(feature = "metrics")]
+        metrics::histogram!("lock_wait_seconds").record(wait.as_secs_f64());
+        #[cfg(feature = "tracing")]
+        tracing::trace!(?wait, "waited for symbol table lock");
+    }
+    guard
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn stats_track_symbol_lifecycle() {
+        let _lock = test_lock();
+
+        let before = symbol_stats();
+        let s = Symbol::new("counted");
+        let during = symbol_stats();
+        drop(s);
+        // cached weak handle would keep the symbol allocated
+        #[cfg(feature = "thread-cache")]
+        clear_thread_cache();
+        let after = symbol_stats();
+
+        assert_eq!(during.created, before.created + 1);
+        assert_eq!(during.live, before.live + 1);
+        assert!(during.bytes >= before.bytes + "counted".len());
+        assert_eq!(after.freed, before.freed + 1);
+        assert_eq!(after.live, before.live);
+        assert_eq!(after.bytes, before.bytes);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn stats_are_published_through_metrics() {
+        use metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+        use parking_lot::Mutex;
+        use std::collections::HashMap;
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        #[derive(Default)]
+        struct TestRecorder(Mutex<HashMap<String, Arc<AtomicU64>>>);
+
+        impl TestRecorder {
+            fn value(&self, name: &str) -> Arc<AtomicU64> {
+                self.0.lock().entry(name.to_string()).or_default().clone()
+            }
+        }
+
+        impl Recorder for TestRecorder {
+            fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+            fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+                Counter::from_arc(self.value(key.name()))
+            }
+
+            fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+                Gauge::from_arc(self.value(key.name()))
+            }
+
+            fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+                Histogram::noop()
+            }
+        }
+
+        let _lock = test_lock();
+
+        let recorder = TestRecorder::default();
+        metrics::with_local_recorder(&recorder, || {
+            let s = Symbol::new("metered");
+            assert_eq!(f64::from_bits(recorder.value("symbols_live").load(Ordering::Relaxed)), 1.0);
+            assert!(f64::from_bits(recorder.value("bytes_in_use").load(Ordering::Relaxed)) >= "metered".len() as f64);
+            drop(s);
+            #[cfg(feature = "thread-cache")]
+            clear_thread_cache();
+        });
+
+        assert_eq!(recorder.value("symbols_created_total").load(Ordering::Relaxed), 1);
+        assert_eq!(f64::from_bits(recorder.value("symbols_live").load(Ordering::Relaxed)), 0.0);
+        assert_eq!(f64::from_bits(recorder.value("bytes_in_use").load(Ordering::Relaxed)), 0.0);
+    }
+}
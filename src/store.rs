@@ -0,0 +1,428 @@
+use super::Symbol;
+
+use std::collections::{BTreeSet, HashMap};
+use std::convert::TryFrom;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+const MAGIC: &[u8; 8] = b"KGSYMST\x01";
+
+const HEADER_SIZE: usize = 16;
+
+/// Builder of a read-only symbol store file.
+///
+/// Store file contains sorted, unique strings. Store layout (all integers are little-endian `u64`):
+/// magic, string count `n`, `n + 1` offsets into string data, and string data.
+#[derive(Debug, Default, Clone)]
+pub struct SymbolStoreBuilder {
+    strings: BTreeSet<String>,
+}
+
+impl SymbolStoreBuilder {
+    pub fn new() -> Self {
+        SymbolStoreBuilder {
+            strings: BTreeSet::new(),
+        }
+    }
+
+    pub fn insert<S: AsRef<str>>(&mut self, value: S) {
+        if !self.strings.contains(value.as_ref()) {
+            self.strings.insert(value.as_ref().to_string());
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    pub fn write_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&(self.strings.len() as u64).to_le_bytes())?;
+        let mut offset = 0u64;
+        w.write_all(&offset.to_le_bytes())?;
+        for s in self.strings.iter() {
+            offset += s.len() as u64;
+            w.write_all(&offset.to_le_bytes())?;
+        }
+        for s in self.strings.iter() {
+            w.write_all(s.as_bytes())?;
+        }
+        w.flush()
+    }
+
+    /// Writes store to a temporary file and renames it to `path`, so that stores which
+    /// have the previous file mapped are not affected.
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(format!(".{}.tmp", std::process::id()));
+        let res = self.write_to(io::BufWriter::new(std::fs::File::create(&tmp)?))
+            .and_then(|_| std::fs::rename(&tmp, path));
+        if res.is_err() {
+            let _ = std::fs::remove_file(&tmp);
+        }
+        res
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        self.write_to(&mut data).unwrap();
+        data
+    }
+}
+
+impl<S: AsRef<str>> Extend<S> for SymbolStoreBuilder {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, iter: I) {
+        for s in iter {
+            self.insert(s);
+        }
+    }
+}
+
+
+enum StoreData {
+    Owned(Vec<u8>),
+    #[cfg(all(unix, feature = "mmap"))]
+    Mapped(mmap::Mmap),
+}
+
+impl AsRef<[u8]> for StoreData {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            StoreData::Owned(data) => data,
+            #[cfg(all(unix, feature = "mmap"))]
+            StoreData::Mapped(data) => data.as_ref(),
+        }
+    }
+}
+
+/// Read-only store of sorted strings, identified by their position in the store.
+///
+/// With `mmap` feature enabled store files are memory-mapped, so multiple processes
+/// opening the same file share its memory. Contents of the store are validated only once,
+/// when it is opened, so a mapped file must not be modified while the store is open.
+/// Files should be replaced atomically instead, like [`SymbolStoreBuilder::write_file`] does.
+pub struct SymbolStore {
+    data: StoreData,
+    count: usize,
+}
+
+impl SymbolStore {
+    /// Opens store file, memory-mapping it if `mmap` feature is enabled.
+    ///
+    /// Memory-mapped file must not be modified until the store is dropped, see [`SymbolStore`].
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<SymbolStore> {
+        #[cfg(all(unix, feature = "mmap"))]
+        return SymbolStore::new(StoreData::Mapped(mmap::Mmap::open(path.as_ref())?));
+
+        #[cfg(not(all(unix, feature = "mmap")))]
+        return SymbolStore::new(StoreData::Owned(std::fs::read(path)?));
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> io::Result<SymbolStore> {
+        SymbolStore::new(StoreData::Owned(data))
+    }
+
+    fn new(data: StoreData) -> io::Result<SymbolStore> {
+        fn invalid(msg: &str) -> io::Error {
+            io::Error::new(io::ErrorKind::InvalidData, format!("invalid symbol store: {}", msg))
+        }
+
+        let bytes = data.as_ref();
+        if bytes.len() < HEADER_SIZE || &bytes[..8] != MAGIC {
+            return Err(invalid("bad header"));
+        }
+        let count = usize::try_from(read_u64(bytes, 8))
+            .ok()
+            .filter(|&n| n <= u32::MAX as usize)
+            .ok_or_else(|| invalid("too many strings"))?;
+        let data_start = count.checked_add(1)
+            .and_then(|n| n.checked_mul(8))
+            .and_then(|n| n.checked_add(HEADER_SIZE))
+            .filter(|&n| n <= bytes.len())
+            .ok_or_else(|| invalid("offsets table out of bounds"))?;
+
+        let mut prev: Option<&str> = None;
+        let offset = |i: usize| usize::try_from(read_u64(bytes, HEADER_SIZE + i * 8))
+            .ok()
+            .and_then(|o| data_start.checked_add(o))
+            .filter(|&o| o <= bytes.len())
+            .ok_or_else(|| invalid("string offset out of bounds"));
+        let mut start = offset(0)?;
+        for i in 0..count {
+            let end = offset(i + 1)?;
+            if start > end {
+                return Err(invalid("string offset out of bounds"));
+            }
+            let s = std::str::from_utf8(&bytes[start..end])
+                .map_err(|_| invalid("string is not valid UTF-8"))?;
+            if prev.is_some_and(|p| p >= s) {
+                return Err(invalid("strings are not sorted"));
+            }
+            prev = Some(s);
+            start = end;
+        }
+
+        Ok(SymbolStore { data, count })
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns string with given id.
+    pub fn get(&self, id: u32) -> Option<&str> {
+        let id = id as usize;
+        if id < self.count {
+            Some(self.string_at(id))
+        } else {
+            None
+        }
+    }
+
+    /// Returns id of given string, if it is present in the store.
+    pub fn find(&self, value: &str) -> Option<u32> {
+        let (mut lo, mut hi) = (0, self.count);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match self.string_at(mid).cmp(value) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => return Some(mid as u32),
+            }
+        }
+        None
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        (0..self.count).map(move |i| self.string_at(i))
+    }
+
+    fn string_at(&self, index: usize) -> &str {
+        let bytes = self.data.as_ref();
+        let data_start = HEADER_SIZE + (self.count + 1) * 8;
+        let start = read_u64(bytes, HEADER_SIZE + index * 8) as usize;
+        let end = read_u64(bytes, HEADER_SIZE + (index + 1) * 8) as usize;
+        // bounds and UTF-8 validity were checked when the store was opened
+        unsafe { std::str::from_utf8_unchecked(&bytes[data_start + start..data_start + end]) }
+    }
+}
+
+impl std::fmt::Debug for SymbolStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SymbolStore")
+            .field("len", &self.count)
+            .finish()
+    }
+}
+
+#[inline]
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[offset..offset + 8]);
+    u64::from_le_bytes(buf)
+}
+
+
+/// Shared [`SymbolStore`] combined with a per-process overlay for strings missing in the store.
+///
+/// Strings from the store are identified by their store ids, strings from the overlay
+/// get subsequent ids, starting at store length.
+#[derive(Debug)]
+pub struct SymbolStoreOverlay {
+    store: Arc<SymbolStore>,
+    symbols: Vec<Symbol>,
+    ids: HashMap<Symbol, u32>,
+}
+
+impl SymbolStoreOverlay {
+    pub fn new(store: Arc<SymbolStore>) -> Self {
+        SymbolStoreOverlay {
+            store,
+            symbols: Vec::new(),
+            ids: HashMap::new(),
+        }
+    }
+
+    pub fn store(&self) -> &Arc<SymbolStore> {
+        &self.store
+    }
+
+    pub fn len(&self) -> usize {
+        self.store.len() + self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns number of strings interned in the overlay.
+    pub fn overlay_len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn find(&self, value: &str) -> Option<u32> {
+        self.store.find(value).or_else(|| self.ids.get(value).cloned())
+    }
+
+    pub fn intern(&mut self, value: &str) -> u32 {
+        if let Some(id) = self.find(value) {
+            return id;
+        }
+        let id = u32::try_from(self.store.len() + self.symbols.len()).expect("symbol store overlay id overflow");
+        let s = Symbol::new(value);
+        self.symbols.push(s.clone());
+        self.ids.insert(s, id);
+        id
+    }
+
+    pub fn get(&self, id: u32) -> Option<&str> {
+        let index = id as usize;
+        if index < self.store.len() {
+            self.store.get(id)
+        } else {
+            self.symbols.get(index - self.store.len()).map(|s| s.as_ref())
+        }
+    }
+}
+
+
+#[cfg(all(unix, feature = "mmap"))]
+mod mmap {
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+
+    pub struct Mmap {
+        ptr: *mut libc::c_void,
+        len: usize,
+    }
+
+    impl Mmap {
+        pub fn open(path: &Path) -> io::Result<Mmap> {
+            let file = std::fs::File::open(path)?;
+            let len = file.metadata()?.len() as usize;
+            if len == 0 {
+                return Ok(Mmap { ptr: std::ptr::null_mut(), len });
+            }
+            let ptr = unsafe {
+                // private mapping, so that the store is never written through; the file itself
+                // must still not be modified while mapped, see `SymbolStore`
+                libc::mmap(std::ptr::null_mut(), len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0)
+            };
+            if ptr == libc::MAP_FAILED {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(Mmap { ptr, len })
+            }
+        }
+    }
+
+    impl AsRef<[u8]> for Mmap {
+        fn as_ref(&self) -> &[u8] {
+            if self.len == 0 {
+                &[]
+            } else {
+                unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+            }
+        }
+    }
+
+    impl Drop for Mmap {
+        fn drop(&mut self) {
+            if self.len > 0 {
+                unsafe {
+                    libc::munmap(self.ptr, self.len);
+                }
+            }
+        }
+    }
+
+    unsafe impl Send for Mmap {}
+
+    unsafe impl Sync for Mmap {}
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    use std::sync::Arc;
+
+    fn builder() -> SymbolStoreBuilder {
+        let mut b = SymbolStoreBuilder::new();
+        b.extend(vec!["gamma", "alpha", "beta", "alpha", ""]);
+        b
+    }
+
+    #[test]
+    fn store_lookups() {
+        let store = SymbolStore::from_bytes(builder().build()).unwrap();
+
+        assert_eq!(store.len(), 4);
+        assert_eq!(store.iter().collect::<Vec<_>>(), ["", "alpha", "beta", "gamma"]);
+        assert_eq!(store.find("beta"), Some(2));
+        assert_eq!(store.find("delta"), None);
+        assert_eq!(store.get(3), Some("gamma"));
+        assert_eq!(store.get(4), None);
+    }
+
+    #[test]
+    fn store_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("kg-symbol-store-{}.bin", std::process::id()));
+        builder().write_file(&path).unwrap();
+        let store = SymbolStore::open(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let store = store.unwrap();
+        assert_eq!(store.find("gamma"), Some(3));
+        assert_eq!(store.get(1), Some("alpha"));
+    }
+
+    #[test]
+    fn invalid_store_is_rejected() {
+        let mut data = builder().build();
+        assert!(SymbolStore::from_bytes(data[..10].to_vec()).is_err());
+
+        let mut overflow = data.clone();
+        overflow[24..32].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(SymbolStore::from_bytes(overflow).is_err());
+
+        let mut too_many = data.clone();
+        too_many[8..16].copy_from_slice(&(u32::MAX as u64 + 1).to_le_bytes());
+        assert!(SymbolStore::from_bytes(too_many).is_err());
+
+        let len = data.len();
+        data[len - 1] = 0xff;
+        assert!(SymbolStore::from_bytes(data).is_err());
+    }
+
+    #[test]
+    fn overlay_assigns_ids_after_store() {
+        let _lock = test_lock();
+
+        let store = Arc::new(SymbolStore::from_bytes(builder().build()).unwrap());
+        let mut overlay = SymbolStoreOverlay::new(store);
+
+        assert_eq!(overlay.intern("beta"), 2);
+        assert_eq!(overlay.intern("delta"), 4);
+        assert_eq!(overlay.intern("epsilon"), 5);
+        assert_eq!(overlay.intern("delta"), 4);
+        assert_eq!(overlay.overlay_len(), 2);
+        assert_eq!(overlay.find("epsilon"), Some(5));
+        assert_eq!(overlay.get(4), Some("delta"));
+        assert_eq!(overlay.get(0), Some(""));
+        assert_eq!(overlay.get(6), None);
+    }
+}
@@ -0,0 +1,169 @@
+use super::{DefaultHashBuilder, Symbol};
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::{Arc, Weak};
+
+use parking_lot::RwLock;
+
+type TupleTable = HashMap<Box<[Symbol]>, Weak<TupleData>, DefaultHashBuilder>;
+
+lazy_static! {
+    static ref TUPLES: RwLock<TupleTable> = RwLock::new(TupleTable::default());
+}
+
+struct TupleData {
+    items: Box<[Symbol]>,
+}
+
+impl Drop for TupleData {
+    fn drop(&mut self) {
+        let mut tuples = TUPLES.write();
+        // entry might have been replaced by a new tuple after the last handle was released
+        if let Some(w) = tuples.get(&self.items) {
+            if w.strong_count() == 0 {
+                tuples.remove(&self.items);
+            }
+        }
+    }
+}
+
+/// Interned sequence of symbols, usable as a composite key.
+///
+/// Equal sequences share a single allocation, so `SymbolTuple` is pointer-sized,
+/// and equality and hashing compare only the pointer.
+#[derive(Clone)]
+pub struct SymbolTuple(Arc<TupleData>);
+
+impl SymbolTuple {
+    pub fn new(items: &[Symbol]) -> SymbolTuple {
+        if let Some(t) = TUPLES.read().get(items).and_then(Weak::upgrade) {
+            return SymbolTuple(t);
+        }
+
+        let mut tuples = TUPLES.write();
+        if let Some(t) = tuples.get(items).and_then(Weak::upgrade) {
+            return SymbolTuple(t);
+        }
+        let items: Box<[Symbol]> = items.into();
+        let t = Arc::new(TupleData { items: items.clone() });
+        tuples.insert(items, Arc::downgrade(&t));
+        SymbolTuple(t)
+    }
+
+    /// Returns existing tuple of `items`, without interning a new one.
+    pub fn get(items: &[Symbol]) -> Option<SymbolTuple> {
+        TUPLES.read().get(items).and_then(Weak::upgrade).map(SymbolTuple)
+    }
+
+    pub fn as_slice(&self) -> &[Symbol] {
+        &self.0.items
+    }
+}
+
+impl Deref for SymbolTuple {
+    type Target = [Symbol];
+
+    fn deref(&self) -> &[Symbol] {
+        self.as_slice()
+    }
+}
+
+impl PartialEq for SymbolTuple {
+    fn eq(&self, other: &SymbolTuple) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SymbolTuple {}
+
+impl Hash for SymbolTuple {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state)
+    }
+}
+
+impl PartialOrd for SymbolTuple {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SymbolTuple {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.eq(other) {
+            Ordering::Equal
+        } else {
+            self.as_slice().cmp(other.as_slice())
+        }
+    }
+}
+
+impl<'a> From<&'a [Symbol]> for SymbolTuple {
+    fn from(items: &'a [Symbol]) -> Self {
+        SymbolTuple::new(items)
+    }
+}
+
+impl std::iter::FromIterator<Symbol> for SymbolTuple {
+    fn from_iter<T: IntoIterator<Item = Symbol>>(iter: T) -> Self {
+        let items: Vec<Symbol> = iter.into_iter().collect();
+        SymbolTuple::new(&items)
+    }
+}
+
+impl std::fmt::Debug for SymbolTuple {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut t = f.debug_tuple("");
+        for s in self.as_slice() {
+            t.field(s);
+        }
+        t.finish()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    fn syms(items: &[&str]) -> Vec<Symbol> {
+        items.iter().map(|s| Symbol::from(*s)).collect()
+    }
+
+    #[test]
+    fn tuple_sizeof_is_equal_to_pointer() {
+        assert_eq!(std::mem::size_of::<SymbolTuple>(), std::mem::size_of::<usize>());
+    }
+
+    #[test]
+    fn equal_sequences_share_identity() {
+        let _lock = test_lock();
+
+        let t1 = SymbolTuple::new(&syms(&["ns", "name", "1.0"]));
+        let t2: SymbolTuple = syms(&["ns", "name", "1.0"]).into_iter().collect();
+        let t3 = SymbolTuple::new(&syms(&["ns", "name", "2.0"]));
+
+        assert_eq!(t1, t2);
+        assert_ne!(t1, t3);
+        assert!(t1 < t3);
+        assert_eq!(t1.len(), 3);
+        assert_eq!(t1[1], "name");
+        assert_eq!(format!("{:?}", t1), r#"("ns", "name", "1.0")"#);
+    }
+
+    #[test]
+    fn tuples_are_released_with_last_handle() {
+        let _lock = test_lock();
+
+        let items = syms(&["released", "tuple"]);
+        let t = SymbolTuple::new(&items);
+        assert_eq!(SymbolTuple::get(&items), Some(t.clone()));
+        drop(t);
+        assert_eq!(SymbolTuple::get(&items), None);
+        assert!(super::TUPLES.read().get(&items[..]).is_none());
+    }
+}
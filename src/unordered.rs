@@ -0,0 +1,218 @@
+use super::{BuildIdentityHasher, IdentityKey, Symbol};
+
+use std::collections::HashMap;
+use std::collections::hash_map;
+use std::iter::FromIterator;
+use heapsize::HeapSizeOf;
+
+type Iter<'a, V> = std::iter::Map<hash_map::Iter<'a, IdentityKey, V>, fn((&'a IdentityKey, &'a V)) -> (&'a Symbol, &'a V)>;
+type IterMut<'a, V> = std::iter::Map<hash_map::IterMut<'a, IdentityKey, V>, fn((&'a IdentityKey, &'a mut V)) -> (&'a Symbol, &'a mut V)>;
+type Keys<'a, V> = std::iter::Map<hash_map::Keys<'a, IdentityKey, V>, fn(&'a IdentityKey) -> &'a Symbol>;
+type IntoIter<V> = std::iter::Map<hash_map::IntoIter<IdentityKey, V>, fn((IdentityKey, V)) -> (Symbol, V)>;
+
+/// Hash map keyed by symbols, without preserving insertion order.
+///
+/// Unlike [`SymbolMap`](crate::SymbolMap), it does not keep entries in a separate vector,
+/// so removals are cheap and memory overhead is lower, but positional access is not available.
+/// Keys are hashed by address (see [`IdentityKey`]), so lookups by string first resolve
+/// the string in the global symbol table, like with `SymbolMap`.
+pub struct UnorderedSymbolMap<V> {
+    map: HashMap<IdentityKey, V, BuildIdentityHasher>,
+}
+
+impl<V> UnorderedSymbolMap<V> {
+    pub fn new() -> Self {
+        UnorderedSymbolMap {
+            map: HashMap::default(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        UnorderedSymbolMap {
+            map: HashMap::with_capacity_and_hasher(capacity, Default::default()),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.map.shrink_to_fit();
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.map.reserve(additional);
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    pub fn contains_key<Q: AsRef<str> + ?Sized>(&self, k: &Q) -> bool {
+        Symbol::get(k.as_ref()).is_some_and(|s| self.map.contains_key(IdentityKey::from_ref(&s)))
+    }
+
+    pub fn get<Q: AsRef<str> + ?Sized>(&self, k: &Q) -> Option<&V> {
+        Symbol::get(k.as_ref()).and_then(|s| self.map.get(IdentityKey::from_ref(&s)))
+    }
+
+    pub fn get_mut<Q: AsRef<str> + ?Sized>(&mut self, k: &Q) -> Option<&mut V> {
+        Symbol::get(k.as_ref()).and_then(move |s| self.map.get_mut(IdentityKey::from_ref(&s)))
+    }
+
+    pub fn insert(&mut self, k: Symbol, v: V) -> Option<V> {
+        self.map.insert(IdentityKey(k), v)
+    }
+
+    pub fn remove<Q: AsRef<str> + ?Sized>(&mut self, k: &Q) -> Option<V> {
+        Symbol::get(k.as_ref()).and_then(|s| self.map.remove(IdentityKey::from_ref(&s)))
+    }
+
+    pub fn remove_entry<Q: AsRef<str> + ?Sized>(&mut self, k: &Q) -> Option<(Symbol, V)> {
+        Symbol::get(k.as_ref())
+            .and_then(|s| self.map.remove_entry(IdentityKey::from_ref(&s)))
+            .map(|(k, v)| (k.0, v))
+    }
+
+    pub fn entry(&mut self, k: Symbol) -> hash_map::Entry<'_, IdentityKey, V> {
+        self.map.entry(IdentityKey(k))
+    }
+
+    pub fn iter(&self) -> Iter<'_, V> {
+        self.map.iter().map(|(k, v)| (&k.0, v))
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, V> {
+        self.map.iter_mut().map(|(k, v)| (&k.0, v))
+    }
+
+    pub fn keys(&self) -> Keys<'_, V> {
+        self.map.keys().map(|k| &k.0)
+    }
+
+    pub fn values(&self) -> hash_map::Values<'_, IdentityKey, V> {
+        self.map.values()
+    }
+
+    pub fn values_mut(&mut self) -> hash_map::ValuesMut<'_, IdentityKey, V> {
+        self.map.values_mut()
+    }
+}
+
+impl<V> Default for UnorderedSymbolMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Clone> Clone for UnorderedSymbolMap<V> {
+    fn clone(&self) -> Self {
+        UnorderedSymbolMap {
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<V: PartialEq> PartialEq for UnorderedSymbolMap<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<V: Eq> Eq for UnorderedSymbolMap<V> {}
+
+impl<V: std::fmt::Debug> std::fmt::Debug for UnorderedSymbolMap<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<V: HeapSizeOf> HeapSizeOf for UnorderedSymbolMap<V> {
+    fn heap_size_of_children(&self) -> usize {
+        self.map.heap_size_of_children()
+    }
+}
+
+impl<V> FromIterator<(Symbol, V)> for UnorderedSymbolMap<V> {
+    fn from_iter<I: IntoIterator<Item = (Symbol, V)>>(iter: I) -> Self {
+        let mut map = UnorderedSymbolMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<V> Extend<(Symbol, V)> for UnorderedSymbolMap<V> {
+    fn extend<I: IntoIterator<Item = (Symbol, V)>>(&mut self, iter: I) {
+        self.map.extend(iter.into_iter().map(|(k, v)| (IdentityKey(k), v)))
+    }
+}
+
+impl<V> IntoIterator for UnorderedSymbolMap<V> {
+    type Item = (Symbol, V);
+    type IntoIter = IntoIter<V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter().map(|(k, v)| (k.0, v))
+    }
+}
+
+impl<'a, V> IntoIterator for &'a UnorderedSymbolMap<V> {
+    type Item = (&'a Symbol, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn unordered_map_smoke_test() {
+        let _lock = test_lock();
+
+        let mut m = UnorderedSymbolMap::new();
+        for i in 0..20 {
+            m.insert(Symbol::new(format!("key{}", i)), i);
+        }
+        m.insert("key1".into(), 100);
+
+        assert_eq!(m.len(), 20);
+        assert_eq!(m.get("key1"), Some(&100));
+        assert_eq!(m.remove("key2"), Some(2));
+        assert_eq!(m.get("key2"), None);
+        assert!(!m.contains_key("missing"));
+        *m.entry("key3".into()).or_insert(0) += 1;
+        assert_eq!(m.get("key3"), Some(&4));
+        assert_eq!(m.values().sum::<i32>(), (0..20).sum::<i32>() + 99 - 2 + 1);
+    }
+
+    #[test]
+    fn lookups_by_symbol_and_string() {
+        let _lock = test_lock();
+
+        let key = Symbol::from("unordered-key");
+        let mut m: UnorderedSymbolMap<i32> = vec![(key.clone(), 1), ("other".into(), 2)].into_iter().collect();
+
+        assert_eq!(m.get(&key), Some(&1));
+        assert_eq!(m.get(&"unordered-key".to_string()), Some(&1));
+        assert!(m.iter().any(|(k, &v)| k == &key && v == 1));
+        assert_eq!(m.remove_entry("other"), Some(("other".into(), 2)));
+
+        let entries: Vec<(Symbol, i32)> = m.into_iter().collect();
+        assert_eq!(entries, [(key, 1)]);
+    }
+}
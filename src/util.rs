@@ -0,0 +1,268 @@
+use super::{normalize, Symbol, SYMBOLS};
+use super::format::intern_buffered;
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::ptr::NonNull;
+
+/// Sorts symbols by their string values.
+///
+/// Strings are compared only between distinct symbols, identical symbols are grouped
+/// by pointer, which makes sorting large slices with many repetitions considerably cheaper.
+pub fn sort_symbols(symbols: &mut [Symbol]) {
+    if symbols.len() < 2 {
+        return;
+    }
+
+    let ranks: HashMap<NonNull<u8>, usize> = {
+        let mut seen = HashSet::new();
+        let mut distinct: Vec<&Symbol> = symbols.iter().filter(|s| seen.insert(s.0)).collect();
+        distinct.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
+        distinct.iter().enumerate().map(|(i, s)| (s.0, i)).collect()
+    };
+
+    symbols.sort_by_cached_key(|s| ranks[&s.0]);
+}
+
+/// Joins strings with a separator into a symbol.
+///
+/// The string is built in a reusable thread-local buffer, so only the symbol itself is allocated.
+pub fn join<I, S>(iter: I, sep: &str) -> Symbol
+    where I: IntoIterator<Item = S>, S: AsRef<str>
+{
+    intern_buffered(|buf| {
+        for (i, p) in iter.into_iter().enumerate() {
+            if i > 0 {
+                buf.write_str(sep)?;
+            }
+            buf.write_str(p.as_ref())?;
+        }
+        Ok(())
+    })
+}
+
+/// Interns substrings of `input` given by `spans`.
+///
+/// Repeated tokens are deduplicated locally, and each global table shard is locked at most once.
+///
+/// # Panics
+///
+/// Panics if any span is out of bounds or does not lie on `char` boundaries.
+pub fn intern_tokens(input: &str, spans: &[Range<usize>]) -> Vec<Symbol> {
+    let mut local: HashMap<&str, usize> = HashMap::new();
+    let mut distinct = Vec::new();
+    let indices: Vec<usize> = spans.iter().map(|span| {
+        let token = &input[span.clone()];
+        *local.entry(token).or_insert_with(|| {
+            distinct.push(normalize(token));
+            distinct.len() - 1
+        })
+    }).collect();
+    let symbols = SYMBOLS.intern_all(&distinct);
+    indices.into_iter().map(|i| symbols[i].clone()).collect()
+}
+
+/// Returns all live symbols, sorted by their string values.
+pub fn dump() -> Vec<Symbol> {
+    let mut symbols: Vec<Symbol> = SYMBOLS.symbols();
+    sort_symbols(&mut symbols);
+    symbols
+}
+
+/// Returns all live symbols starting with `prefix`, sorted by their string values.
+pub fn symbols_with_prefix(prefix: &str) -> Vec<Symbol> {
+    find(|s| s.starts_with(prefix))
+}
+
+/// Returns all live symbols matching `pred`, sorted by their string values.
+///
+/// `pred` is called with a shard of the global symbol table locked for reading,
+/// so it must not create new symbols.
+pub fn find<F: Fn(&str) -> bool>(pred: F) -> Vec<Symbol> {
+    let mut symbols = SYMBOLS.symbols_matching(pred);
+    sort_symbols(&mut symbols);
+    symbols
+}
+
+/// Returns all live symbols matching glob `pattern`, sorted by their string values.
+///
+/// `*` matches any sequence of characters, `?` matches any single character,
+/// `\` escapes the following character.
+#[cfg(feature = "glob")]
+pub fn find_glob(pattern: &str) -> Vec<Symbol> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    find(|s| glob_match(&pattern, s))
+}
+
+#[cfg(feature = "glob")]
+fn glob_match(pattern: &[char], value: &str) -> bool {
+    let value: Vec<char> = value.chars().collect();
+    let (mut p, mut v) = (0, 0);
+    // position of the last `*` in the pattern, and of the value character it currently ends at
+    let mut backtrack = None;
+    while v < value.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, v));
+                continue;
+            }
+            Some('?') => {
+                p += 1;
+                v += 1;
+                continue;
+            }
+            Some(&c) => {
+                let (c, len) = if c == '\\' && p + 1 < pattern.len() { (pattern[p + 1], 2) } else { (c, 1) };
+                if c == value[v] {
+                    p += len;
+                    v += 1;
+                    continue;
+                }
+            }
+            None => {}
+        }
+        match backtrack {
+            Some((bp, bv)) => {
+                p = bp;
+                v = bv + 1;
+                backtrack = Some((bp, bv + 1));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Returns strings and reference counts of all live symbols, except permanent ones,
+/// sorted by string. Intended to be called at process exit to find symbols which are never released.
+pub fn leak_report() -> Vec<(String, usize)> {
+    dump().into_iter()
+        .filter(|s| !s.is_permanent())
+        // reference held by the dump itself is not counted
+        .map(|s| (s.as_str().to_string(), s.ref_count() - 1))
+        .collect()
+}
+
+pub trait JoinSymbols {
+    fn join_symbols(self, sep: &str) -> Symbol;
+}
+
+impl<I> JoinSymbols for I
+    where I: Iterator, I::Item: AsRef<str>
+{
+    fn join_symbols(self, sep: &str) -> Symbol {
+        join(self, sep)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn sort_symbols_orders_by_string() {
+        let _lock = test_lock();
+
+        let mut symbols: Vec<Symbol> = ["c", "a", "bb", "a", "b", "c", "a"].iter().map(|&s| s.into()).collect();
+        sort_symbols(&mut symbols);
+
+        assert_eq!(symbols, ["a", "a", "a", "b", "bb", "c", "c"]);
+    }
+
+    #[test]
+    fn sort_symbols_handles_small_slices() {
+        let _lock = test_lock();
+
+        let mut empty: [Symbol; 0] = [];
+        sort_symbols(&mut empty);
+
+        let mut single = [Symbol::from("a")];
+        sort_symbols(&mut single);
+        assert_eq!(single[0], "a");
+    }
+
+    #[test]
+    fn join_with_separator() {
+        let _lock = test_lock();
+
+        let parts = [Symbol::from("a"), Symbol::from("bb"), Symbol::from("ccc")];
+
+        assert_eq!(join(&parts, "."), "a.bb.ccc");
+        assert_eq!(join(vec!["x"], ", "), "x");
+        assert_eq!(join(Vec::<&str>::new(), ", "), "");
+        assert_eq!(parts.iter().join_symbols("::"), "a::bb::ccc");
+        assert_eq!(["x", "y"].iter().join_symbols(""), "xy");
+    }
+
+    #[test]
+    fn intern_tokens_deduplicates_spans() {
+        let _lock = test_lock();
+
+        let input = "let x = x + y";
+        let existing = Symbol::from("y");
+        let tokens = intern_tokens(input, &[0..3, 4..5, 6..7, 8..9, 10..11, 12..13]);
+
+        assert_eq!(tokens, ["let", "x", "=", "x", "+", "y"]);
+        assert_eq!(tokens[1].0, tokens[3].0);
+        assert_eq!(tokens[5].0, existing.0);
+        assert!(intern_tokens(input, &[]).is_empty());
+    }
+
+    #[test]
+    fn prefix_listing() {
+        let _lock = test_lock();
+
+        let _keys: Vec<Symbol> = ["cfg.b", "cfg.a", "cfgx", "log.level"].iter().map(|&s| s.into()).collect();
+
+        assert_eq!(symbols_with_prefix("cfg."), ["cfg.a", "cfg.b"]);
+        assert_eq!(symbols_with_prefix("log.").len(), 1);
+        assert!(symbols_with_prefix("none.").is_empty());
+    }
+
+    #[test]
+    fn find_by_predicate() {
+        let _lock = test_lock();
+
+        let _ids: Vec<Symbol> = ["user_id", "user_name", "group_id"].iter().map(|&s| s.into()).collect();
+
+        assert_eq!(find(|s| s.ends_with("_id")), ["group_id", "user_id"]);
+        assert!(find(|s| s.len() > 100).is_empty());
+    }
+
+    #[cfg(feature = "glob")]
+    #[test]
+    fn find_by_glob() {
+        let _lock = test_lock();
+
+        let _ids: Vec<Symbol> = ["user_id", "user_name", "group_id", "a*b", "ab"].iter().map(|&s| s.into()).collect();
+
+        assert_eq!(find_glob("*_id"), ["group_id", "user_id"]);
+        assert_eq!(find_glob("user_????"), ["user_name"]);
+        assert_eq!(find_glob("a\\*b"), ["a*b"]);
+        assert_eq!(find_glob("*r*_*"), ["group_id", "user_id", "user_name"]);
+        assert!(find_glob("user").is_empty());
+    }
+
+    #[test]
+    fn leak_report_lists_live_symbols() {
+        let _lock = test_lock();
+
+        assert!(leak_report().is_empty());
+        let a = Symbol::new("leaked");
+        let _b = a.clone();
+        let _c = Symbol::new("also leaked");
+        assert_eq!(leak_report(), [("also leaked".to_string(), 1), ("leaked".to_string(), 2)]);
+    }
+
+    #[test]
+    fn dump_is_sorted() {
+        let _lock = test_lock();
+
+        let _symbols: Vec<Symbol> = ["b", "c", "a"].iter().map(|&s| s.into()).collect();
+
+        assert_eq!(dump(), ["", "a", "b", "c"]);
+    }
+}
@@ -0,0 +1,105 @@
+use super::{ref_count_overflow, Symbol, SymbolHdr, MAX_REF_COUNT};
+
+use std::ptr::NonNull;
+use std::sync::atomic::Ordering;
+
+/// Handle to an interned string which does not keep it alive.
+///
+/// The string is removed from the symbol table when the last [`Symbol`] is dropped,
+/// only the symbol header memory is kept until all weak handles are dropped.
+pub struct WeakSymbol(NonNull<u8>);
+
+impl WeakSymbol {
+    #[inline]
+    fn header(&self) -> &SymbolHdr {
+        unsafe { &*(self.0.as_ptr() as *const SymbolHdr) }
+    }
+
+    /// Returns the string, which stays readable until the last weak handle is dropped,
+    /// even after the symbol itself is destroyed.
+    #[cfg(feature = "thread-cache")]
+    #[inline]
+    pub(crate) fn as_str(&self) -> &str {
+        self.header().as_ref()
+    }
+
+    /// Returns symbol if it is still alive.
+    pub fn upgrade(&self) -> Option<Symbol> {
+        unsafe { Symbol::upgrade_raw(self.0) }
+    }
+
+    /// Returns `true` if both handles point to the same symbol.
+    pub fn ptr_eq(&self, other: &WeakSymbol) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Symbol {
+    pub fn downgrade(&self) -> WeakSymbol {
+        if self.header().weak_count.fetch_add(1, Ordering::Relaxed) > MAX_REF_COUNT {
+            ref_count_overflow();
+        }
+        WeakSymbol(self.0)
+    }
+}
+
+impl Clone for WeakSymbol {
+    fn clone(&self) -> Self {
+        if self.header().weak_count.fetch_add(1, Ordering::Relaxed) > MAX_REF_COUNT {
+            ref_count_overflow();
+        }
+        WeakSymbol(self.0)
+    }
+}
+
+impl Drop for WeakSymbol {
+    fn drop(&mut self) {
+        unsafe { Symbol::release_weak(self.0) }
+    }
+}
+
+impl std::fmt::Debug for WeakSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("(WeakSymbol)")
+    }
+}
+
+unsafe impl Send for WeakSymbol {}
+
+unsafe impl Sync for WeakSymbol {}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::tests::test_lock;
+
+    #[test]
+    fn weak_symbol_does_not_keep_string_alive() {
+        let _lock = test_lock();
+
+        let s = Symbol::new("weak");
+        let w1 = s.downgrade();
+        let w2 = w1.clone();
+        assert_eq!(w1.upgrade(), Some(s.clone()));
+        assert!(w1.ptr_eq(&w2));
+
+        drop(s);
+        assert_eq!(w1.upgrade(), None);
+        assert_eq!(Symbol::get("weak"), None);
+
+        let s = Symbol::new("weak");
+        assert_eq!(w2.upgrade(), None);
+        drop(w1);
+        drop(w2);
+        assert_eq!(s, "weak");
+    }
+
+    #[test]
+    fn persistent_symbol_upgrades() {
+        let _lock = test_lock();
+
+        let w = Symbol::empty().downgrade();
+        assert_eq!(w.upgrade(), Some(Symbol::empty()));
+    }
+}
@@ -0,0 +1,26 @@
+//! Freezing is irreversible for the whole process, so it is tested in a separate test binary.
+#![cfg(feature = "global")]
+
+use kg_symbol::*;
+
+#[test]
+fn frozen_table_resolves_existing_symbols_only() {
+    let known = Symbol::new("known");
+    let transient = Symbol::new("transient");
+    let map: SymbolMap<u32> = SymbolMap::new();
+
+    freeze(FreezePolicy::Error);
+    assert!(is_frozen());
+    drop(transient);
+
+    assert_eq!(Symbol::new("known"), known);
+    assert_eq!(Symbol::get("transient").as_ref().map(|s| s.as_str()), Some("transient"));
+    assert_eq!(Symbol::try_new("known"), Ok(known));
+    assert_eq!(Symbol::get("unknown"), None);
+    assert_eq!(Symbol::try_new("unknown"), Err(Error::Frozen));
+    assert!(std::panic::catch_unwind(|| Symbol::new("unknown")).is_err());
+    assert_eq!(map.get("unknown"), None);
+
+    freeze(FreezePolicy::Panic);
+    assert_eq!(Symbol::try_new("unknown"), Err(Error::Frozen));
+}
@@ -0,0 +1,27 @@
+//! Shutdown frees the whole global symbol table, so it is tested in a separate test binary.
+#![cfg(feature = "global")]
+
+use kg_symbol::*;
+
+#[test]
+fn shutdown_frees_all_symbols() {
+    let s = Symbol::new("still referenced");
+    let weak = Symbol::permanent("permanent").downgrade();
+    assert!(std::panic::catch_unwind(|| unsafe { shutdown() }).is_err());
+    drop(s);
+
+    // permanent symbols are freed as well, so their handles must not outlive the shutdown
+    unsafe { shutdown() };
+    let stats = symbol_stats();
+    // only the empty symbol remains
+    assert_eq!(stats.live, 1);
+
+    assert_eq!(Symbol::empty(), "");
+    assert_eq!(Symbol::default(), "");
+    assert!(weak.upgrade().is_none());
+    drop(weak);
+
+    let s = Symbol::new("after shutdown");
+    assert_eq!(Symbol::get("after shutdown"), Some(s));
+    assert_eq!(Symbol::get("permanent"), None);
+}
@@ -0,0 +1,19 @@
+//! Static symbols are installed once per process, so this test runs in its own binary.
+#![cfg(feature = "global")]
+
+use kg_symbol::*;
+
+#[test]
+fn static_symbols_bypass_symbol_table() {
+    let before = Symbol::new("while");
+    let set: PerfectHashSymbolSet = ["if", "else", "while"].iter().map(|&k| Symbol::from(k)).collect();
+    assert!(set_static_symbols(set));
+    assert!(!set_static_symbols(PerfectHashSymbolSet::builder().build()));
+
+    let s = Symbol::new("while");
+    assert!(s.is_permanent());
+    assert_eq!(s, before);
+    assert_eq!(Symbol::get("if"), Some(Symbol::new("if")));
+    assert!(Symbol::get("else").unwrap().is_permanent());
+    assert!(!Symbol::new("loop").is_permanent());
+}
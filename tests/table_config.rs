@@ -0,0 +1,16 @@
+//! Global symbol table can only be configured before it is initialized, so this test runs in its own binary.
+#![cfg(feature = "global")]
+
+use kg_symbol::*;
+
+#[test]
+fn table_is_configured_before_first_use() {
+    assert!(set_table_hasher(TableHasher::Fx));
+    assert!(set_shard_count(3));
+
+    let s = Symbol::new("configured");
+    assert_eq!(Symbol::get("configured"), Some(s));
+    assert_eq!(table_hasher(), TableHasher::Fx);
+    assert_eq!(shard_count(), 4);
+    assert!(!set_table_hasher(TableHasher::Random));
+}